@@ -2,24 +2,32 @@ use crate::token::Token;
 
 pub struct Lexer {
     line: usize,
-    line_position: usize,
+    column: usize,
+    token_position: (usize, usize),
+    /// Byte offset where the current token starts
+    token_start: usize,
     position: usize,
     read_position: usize,
     ch: u8,
     input: Vec<u8>,
     curr_token: Token,
+    /// Whether a line break came between the previous token and the current one
+    newline_before: bool,
 }
 
 impl Lexer {
     pub fn new(input: String) -> Lexer {
         let mut lex = Lexer {
-            line: 0,
-            line_position: 0,
+            line: 1,
+            column: 0,
+            token_position: (1, 1),
+            token_start: 0,
             position: 0,
             read_position: 0,
             ch: 0,
             input: input.into_bytes(),
             curr_token: Token::Illegal,
+            newline_before: false,
         };
 
         lex.read_char();
@@ -36,24 +44,76 @@ impl Lexer {
             b'}' => Token::RSquirly,
             b'(' => Token::Lparen,
             b')' => Token::Rparen,
+            b'[' => Token::LBracket,
+            b']' => Token::RBracket,
             b',' => Token::Comma,
             b';' => Token::Semicolon,
+            b'?' => {
+                if self.peek_char() == b'?' {
+                    self.read_char();
+                    Token::QuestionQuestion
+                } else {
+                    Token::Question
+                }
+            }
+            b':' => Token::Colon,
             b'=' => {
                 if self.peek_char() == b'=' {
                     self.read_char();
-                    Token::Equal
+
+                    if self.peek_char() == b'=' {
+                        self.read_char();
+                        Token::StrictEqual
+                    } else {
+                        Token::Equal
+                    }
                 } else {
                     Token::Assign
                 }
             }
-            b'+' => Token::Plus,
-            b'-' => Token::Minus,
-            b'*' => Token::Asterisk,
-            b'/' => Token::ForwardSlash,
+            b'+' => {
+                if self.peek_char() == b'+' {
+                    self.read_char();
+                    Token::PlusPlus
+                } else {
+                    Token::Plus
+                }
+            }
+            b'-' => {
+                if self.peek_char() == b'-' {
+                    self.read_char();
+                    Token::MinusMinus
+                } else {
+                    Token::Minus
+                }
+            }
+            b'*' => {
+                if self.peek_char() == b'*' {
+                    self.read_char();
+                    Token::StarStar
+                } else {
+                    Token::Asterisk
+                }
+            }
+            b'%' => Token::Percent,
+            b'/' => {
+                if self.peek_char() == b'*' {
+                    if let Err(message) = self.skip_block_comment() {
+                        return Token::Error(message);
+                    }
+
+                    return self.next_token();
+                }
+
+                Token::ForwardSlash
+            }
             b'<' => {
                 if self.peek_char() == b'=' {
                     self.read_char();
                     Token::LessThanOrEqual
+                } else if self.peek_char() == b'<' {
+                    self.read_char();
+                    Token::ShiftLeft
                 } else {
                     Token::LessThan
                 }
@@ -62,6 +122,9 @@ impl Lexer {
                 if self.peek_char() == b'=' {
                     self.read_char();
                     Token::GreaterThanOrEqual
+                } else if self.peek_char() == b'>' {
+                    self.read_char();
+                    Token::ShiftRight
                 } else {
                     Token::GreaterThan
                 }
@@ -71,7 +134,7 @@ impl Lexer {
                     self.read_char();
                     Token::And
                 } else {
-                    Token::Illegal
+                    Token::Ampersand
                 }
             }
             b'|' => {
@@ -79,24 +142,37 @@ impl Lexer {
                     self.read_char();
                     Token::Or
                 } else {
-                    Token::Illegal
+                    Token::Pipe
                 }
             }
+            b'^' => Token::Caret,
+            b'~' => Token::Tilde,
             b'!' => {
                 if self.peek_char() == b'=' {
                     self.read_char();
-                    Token::NotEqual
+
+                    if self.peek_char() == b'=' {
+                        self.read_char();
+                        Token::StrictNotEqual
+                    } else {
+                        Token::NotEqual
+                    }
                 } else {
                     Token::Bang
                 }
             }
-            b'"' => {
+            b'"' | b'\'' => {
+                let delimiter = self.ch;
+
                 // dont know if is the ideal solution lmao
-                self.read_char(); // skip the first "
+                self.read_char(); // skip the opening quote
 
-                let string = self.read_delimiter(b'"');
+                let string = match self.read_delimiter(delimiter) {
+                    Ok(string) => string,
+                    Err(message) => return Token::Error(message),
+                };
 
-                self.read_char(); // skip the last "
+                self.read_char(); // skip the closing quote
 
                 return Token::String(string);
             }
@@ -106,36 +182,48 @@ impl Lexer {
                 return match ident.as_str() {
                     "function" => Token::Function,
                     "let" => Token::Let,
+                    "const" => Token::Const,
                     "if" => Token::If,
                     "else" => Token::Else,
                     "while" => Token::While,
                     "for" => Token::For,
                     "do" => Token::Do,
                     "return" => Token::Return,
+                    "break" => Token::Break,
+                    "continue" => Token::Continue,
+                    "switch" => Token::Switch,
+                    "case" => Token::Case,
+                    "default" => Token::Default,
+                    "try" => Token::Try,
+                    "catch" => Token::Catch,
+                    "finally" => Token::Finally,
+                    "throw" => Token::Throw,
+                    "typeof" => Token::Typeof,
+                    "this" => Token::This,
+                    "in" => Token::In,
                     "true" => Token::True,
                     "false" => Token::False,
                     "null" => Token::Null,
-                    "print" => Token::Print, // temporary
                     _ => Token::Ident(ident),
                 };
             }
+            b'.' if self.peek_char() == b'.'
+                && self.input.get(self.read_position + 1) == Some(&b'.') =>
+            {
+                self.read_char();
+                self.read_char();
+                Token::Ellipsis
+            }
+            b'.' if !self.peek_char().is_ascii_digit() => Token::Dot,
             // FIX: Reads , as a number literal
-            b'0'..=b'9' | b'.' => return Token::Number(self.read_number()),
-            b'\n' => {
-                if self.peek_char() == b'\r' {
-                    self.read_char();
-                }
-
-                self.line += 1;
-                self.line_position = 0;
-
-                Token::Newline
+            b'0'..=b'9' | b'.' => {
+                return match self.read_number() {
+                    Ok(number) => Token::Number(number),
+                    Err(message) => Token::Error(message),
+                };
             }
             0 => Token::Eof,
-            _ => panic!(
-                "Unexpected character at line {} position {}: {}",
-                self.line, self.line_position, self.ch as char
-            ),
+            ch => Token::Error(format!("Unexpected character '{}'", ch as char)),
         };
 
         self.read_char();
@@ -143,21 +231,46 @@ impl Lexer {
         return token;
     }
 
+    /**
+     * Every token of `source` up to the end of input, each with the byte offsets where it starts
+     * and ends (exclusive). Literals span their whole source text, quotes included
+     */
+    pub fn tokenize_all(source: &str) -> Vec<(Token, usize, usize)> {
+        let mut lexer = Lexer::new(source.to_string());
+        let mut tokens = Vec::new();
+
+        loop {
+            let token = lexer.next_token();
+
+            if token == Token::Eof {
+                return tokens;
+            }
+
+            tokens.push((token, lexer.token_start, lexer.position));
+        }
+    }
+
     pub fn next_token(&mut self) -> Token {
+        let line = self.line;
+
         self.skip_whitespace();
 
+        self.token_position = (self.line, self.column);
+        self.token_start = self.position;
+
         let token = self.parse_token();
         self.curr_token = token.clone();
+        // set after parsing, a comment before the token makes parse_token recurse into next_token
+        self.newline_before = self.token_position.0 > line;
 
         return token;
     }
 
+    /**
+     * The next unread byte, the one `read_char` moves to, or 0 past the end of the input
+     */
     pub fn peek_char(&self) -> u8 {
-        if self.position + 1 >= self.input.len() {
-            return 0;
-        } else {
-            return self.input[self.position + 1];
-        }
+        return self.input.get(self.read_position).copied().unwrap_or(0);
     }
 
     pub fn match_token_and_consume(&mut self, token: Token) -> bool {
@@ -169,31 +282,75 @@ impl Lexer {
         return false;
     }
 
-    // dont know how I feel about this method
     pub fn peek_token(&mut self) -> Token {
+        return self.peek().0;
+    }
+
+    /**
+     * Whether a line break separates the current token from the next one, which can end a
+     * statement in place of a semicolon
+     */
+    pub fn newline_before_next(&mut self) -> bool {
+        return self.peek().1;
+    }
+
+    /**
+     * Whether a line break came right before the current token
+     */
+    pub fn newline_before(&self) -> bool {
+        return self.newline_before;
+    }
+
+    // dont know how I feel about this method
+    fn peek(&mut self) -> (Token, bool) {
         let pos = self.position;
         let read_pos = self.read_position;
         let ch = self.ch;
         let current_token = self.curr_token.clone();
+        let (line, column) = (self.line, self.column);
+        let token_position = self.token_position;
+        let token_start = self.token_start;
+        let newline_before = self.newline_before;
 
         let token = self.next_token();
+        let newline = self.newline_before;
         self.position = pos;
         self.read_position = read_pos;
         self.ch = ch;
         self.curr_token = current_token;
+        self.line = line;
+        self.column = column;
+        self.token_position = token_position;
+        self.token_start = token_start;
+        self.newline_before = newline_before;
 
-        token
+        (token, newline)
     }
 
     pub fn curr_token(&self) -> Token {
         return self.curr_token.clone();
     }
 
+    /**
+     * Line and column (both 1-based) where the current token starts
+     */
+    pub fn position(&self) -> (usize, usize) {
+        return self.token_position;
+    }
+
     pub fn is_at_end(&self) -> bool {
         return self.read_position >= self.input.len();
     }
 
     fn read_char(&mut self) {
+        // \n and a bare \r (not the start of \r\n) both end a line
+        if self.ch == b'\n' || (self.ch == b'\r' && self.peek_char() != b'\n') {
+            self.line += 1;
+            self.column = 0;
+        }
+
+        self.column += 1;
+
         if self.is_at_end() {
             self.ch = 0;
         } else {
@@ -210,27 +367,139 @@ impl Lexer {
         }
     }
 
+    // Consumes a block comment, leaving the lexer on the char right after the closing "*/"
+    fn skip_block_comment(&mut self) -> Result<(), String> {
+        self.read_char(); // skip the /
+        self.read_char(); // skip the *
+
+        loop {
+            match self.ch {
+                0 if self.is_at_end() => return Err("Unterminated block comment".to_string()),
+                b'*' if self.peek_char() == b'/' => {
+                    self.read_char();
+                    self.read_char();
+
+                    return Ok(());
+                }
+                b'/' if self.peek_char() == b'*' => {
+                    return Err("Nested block comments are not supported".to_string())
+                }
+                _ => self.read_char(),
+            }
+        }
+    }
+
     fn read_ident(&mut self) -> String {
         let pos = self.position;
 
-        while self.ch.is_ascii_alphabetic() || self.ch == b'_' {
+        // digits are only allowed after the first char, which next_token already checked
+        while self.ch.is_ascii_alphanumeric() || self.ch == b'_' {
             self.read_char();
         }
 
         return String::from_utf8_lossy(&self.input[pos..self.position]).to_string();
     }
 
-    fn read_delimiter(&mut self, delimiter: u8) -> String {
-        let pos = self.position;
+    /**
+     * Reads until the delimiter, decoding escape sequences along the way
+     */
+    fn read_delimiter(&mut self, delimiter: u8) -> Result<String, String> {
+        let mut bytes = Vec::new();
 
         while self.ch != delimiter {
+            if self.ch == 0 && self.is_at_end() {
+                return Err("Unterminated string literal".to_string());
+            }
+
+            if self.ch == b'\\' {
+                self.read_char(); // skip the \
+
+                if self.ch == b'x' || self.ch == b'u' {
+                    let ch = self.read_code_point_escape()?;
+
+                    bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                    self.read_char();
+
+                    continue;
+                }
+
+                bytes.push(match self.ch {
+                    b'n' => b'\n',
+                    b't' => b'\t',
+                    b'r' => b'\r',
+                    b'0' => b'\0',
+                    b'\\' => b'\\',
+                    b'"' => b'"',
+                    b'\'' => b'\'',
+                    0 if self.is_at_end() => return Err("Unterminated string literal".to_string()),
+                    ch => return Err(format!("Unknown escape sequence '\\{}'", ch as char)),
+                });
+            } else {
+                bytes.push(self.ch);
+            }
+
             self.read_char();
         }
 
-        return String::from_utf8_lossy(&self.input[pos..self.position]).to_string();
+        return Ok(String::from_utf8_lossy(&bytes).to_string());
+    }
+
+    /**
+     * Decodes `\xHH` or `\u{H...}` with the lexer on the `x` or `u`, leaving it on the escape's
+     * last character
+     */
+    fn read_code_point_escape(&mut self) -> Result<char, String> {
+        let mut digits = String::new();
+
+        if self.ch == b'x' {
+            for _ in 0..2 {
+                self.read_char();
+                digits.push(self.ch as char);
+            }
+        } else {
+            self.read_char();
+
+            if self.ch != b'{' {
+                return Err("Expected '{' after '\\u' in unicode escape sequence".to_string());
+            }
+
+            self.read_char();
+
+            while self.ch != b'}' {
+                if self.ch == 0 && self.is_at_end() {
+                    return Err("Unterminated string literal".to_string());
+                }
+
+                if self.ch == b'"' || self.ch == b'\'' {
+                    return Err("Expected '}' to close unicode escape sequence".to_string());
+                }
+
+                digits.push(self.ch as char);
+                self.read_char();
+            }
+        }
+
+        let code_point = match u32::from_str_radix(&digits, 16) {
+            Ok(code_point) if digits.chars().all(|ch| ch.is_ascii_hexdigit()) => code_point,
+            _ => return Err(format!("Invalid hexadecimal escape sequence '{}'", digits)),
+        };
+
+        return char::from_u32(code_point)
+            .ok_or_else(|| format!("Invalid code point {:X} in escape sequence", code_point));
     }
 
-    fn read_number(&mut self) -> String {
+    fn read_number(&mut self) -> Result<String, String> {
+        if self.ch == b'0' {
+            if let Some(radix) = match self.peek_char() {
+                b'x' | b'X' => Some(16),
+                b'o' | b'O' => Some(8),
+                b'b' | b'B' => Some(2),
+                _ => None,
+            } {
+                return self.read_radix_number(radix);
+            }
+        }
+
         let pos = self.position;
         let mut has_dot = false;
 
@@ -242,7 +511,51 @@ impl Lexer {
             self.read_char();
         }
 
-        return String::from_utf8_lossy(&self.input[pos..self.position]).to_string();
+        return Ok(String::from_utf8_lossy(&self.input[pos..self.position]).to_string());
+    }
+
+    /**
+     * Reads a prefixed integer literal such as 0xFF, 0o17 or 0b1010, keeping the prefix
+     */
+    fn read_radix_number(&mut self, radix: u32) -> Result<String, String> {
+        let pos = self.position;
+
+        self.read_char(); // skip the 0
+        self.read_char(); // skip the radix letter
+
+        let digits_pos = self.position;
+
+        while (self.ch as char).is_digit(radix) {
+            self.read_char();
+        }
+
+        if self.ch.is_ascii_alphanumeric() {
+            return Err(format!(
+                "Invalid digit '{}' in base {} literal",
+                self.ch as char, radix
+            ));
+        }
+
+        if digits_pos == self.position {
+            return Err(format!("Missing digits in base {} literal", radix));
+        }
+
+        return Ok(String::from_utf8_lossy(&self.input[pos..self.position]).to_string());
+    }
+}
+
+/**
+ * Yields every token up to and including `Token::Eof`
+ */
+impl Iterator for Lexer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.curr_token == Token::Eof {
+            return None;
+        }
+
+        return Some(self.next_token());
     }
 }
 
@@ -270,6 +583,141 @@ mod test {
         }
     }
 
+    #[test]
+    fn read_single_quoted() {
+        let input = r#"let s = 'hello world';"#;
+        let mut lex = Lexer::new(input.into());
+
+        let tokens = vec![
+            Token::Let,
+            Token::ident("s"),
+            Token::Assign,
+            Token::string("hello world"),
+            Token::Semicolon,
+        ];
+
+        for token in tokens {
+            assert_eq!(token, lex.next_token());
+        }
+    }
+
+    #[test]
+    fn read_mixed_quotes() {
+        let input = r#"'say "hi"' + "it's" + 'it\'s';"#;
+        let mut lex = Lexer::new(input.into());
+
+        assert_eq!(lex.next_token(), Token::string("say \"hi\""));
+        assert_eq!(lex.next_token(), Token::Plus);
+        assert_eq!(lex.next_token(), Token::string("it's"));
+        assert_eq!(lex.next_token(), Token::Plus);
+        assert_eq!(lex.next_token(), Token::string("it's"));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+    }
+
+    #[test]
+    fn read_unterminated_string() {
+        let mut lex = Lexer::new("let s = \"hello".into());
+
+        assert_eq!(lex.next_token(), Token::Let);
+        assert_eq!(lex.next_token(), Token::ident("s"));
+        assert_eq!(lex.next_token(), Token::Assign);
+        assert_eq!(
+            lex.next_token(),
+            Token::error("Unterminated string literal")
+        );
+        assert_eq!(lex.position(), (1, 9));
+        assert_eq!(lex.next_token(), Token::Eof);
+
+        let mut lex = Lexer::new("'trailing escape\\".into());
+
+        assert_eq!(
+            lex.next_token(),
+            Token::error("Unterminated string literal")
+        );
+    }
+
+    #[test]
+    fn read_escape_sequences() {
+        let input = r#""line\nbreak" "tab\there" "\r\0" "back\\slash" "say \"hi\"";"#;
+        let mut lex = Lexer::new(input.into());
+
+        assert_eq!(lex.next_token(), Token::string("line\nbreak"));
+        assert_eq!(lex.next_token(), Token::string("tab\there"));
+        assert_eq!(lex.next_token(), Token::string("\r\0"));
+        assert_eq!(lex.next_token(), Token::string("back\\slash"));
+        assert_eq!(lex.next_token(), Token::string("say \"hi\""));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+    }
+
+    #[test]
+    fn read_escape_sequences_bytes() {
+        let mut lex = Lexer::new(r#""a\nb\\""#.into());
+
+        if let Token::String(string) = lex.next_token() {
+            assert_eq!(string.as_bytes(), b"a\nb\\");
+        } else {
+            panic!("Expected a string token");
+        }
+    }
+
+    #[test]
+    fn read_code_point_escapes() {
+        let input = r#""\x41" "\u{48}\u{69}" "\u{1F600}!" '\xe9\u{E9}'"#;
+        let mut lex = Lexer::new(input.into());
+
+        assert_eq!(lex.next_token(), Token::string("A"));
+        assert_eq!(lex.next_token(), Token::string("Hi"));
+        assert_eq!(lex.next_token(), Token::string("😀!"));
+        assert_eq!(lex.next_token(), Token::string("éé"));
+        assert_eq!(lex.next_token(), Token::Eof);
+    }
+
+    #[test]
+    fn read_malformed_code_point_escapes() {
+        let cases = [
+            (r#""\x4""#, "Invalid hexadecimal escape sequence '4\"'"),
+            (r#""\xZZ""#, "Invalid hexadecimal escape sequence 'ZZ'"),
+            (
+                r#""\u0041""#,
+                "Expected '{' after '\\u' in unicode escape sequence",
+            ),
+            (r#""\u{}""#, "Invalid hexadecimal escape sequence ''"),
+            (r#""\u{+41}""#, "Invalid hexadecimal escape sequence '+41'"),
+            (
+                r#""\u{41""#,
+                "Expected '}' to close unicode escape sequence",
+            ),
+            (
+                r#""\u{110000}""#,
+                "Invalid code point 110000 in escape sequence",
+            ),
+            (
+                r#""\u{D800}""#,
+                "Invalid code point D800 in escape sequence",
+            ),
+            (r#""\u{41"#, "Unterminated string literal"),
+        ];
+
+        for (input, error) in cases {
+            let mut lex = Lexer::new(input.into());
+
+            assert_eq!(lex.next_token(), Token::error(error), "{}", input);
+        }
+    }
+
+    #[test]
+    fn read_unknown_escape_sequence() {
+        let mut lex = Lexer::new(r#"a = "\q";"#.into());
+
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::Assign);
+        assert_eq!(
+            lex.next_token(),
+            Token::error("Unknown escape sequence '\\q'")
+        );
+        assert_eq!(lex.position(), (1, 5));
+    }
+
     #[test]
     fn read_int() {
         let input = r#"123;"#;
@@ -279,9 +727,52 @@ mod test {
         assert_eq!(lex.next_token(), Token::Semicolon);
     }
 
+    #[test]
+    fn read_radix_int() {
+        let input = "0xFF 0b1010 0o17 0XaB 0;";
+        let mut lex = Lexer::new(input.into());
+
+        assert_eq!(lex.next_token(), Token::number("0xFF"));
+        assert_eq!(lex.next_token(), Token::number("0b1010"));
+        assert_eq!(lex.next_token(), Token::number("0o17"));
+        assert_eq!(lex.next_token(), Token::number("0XaB"));
+        assert_eq!(lex.next_token(), Token::number("0"));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+    }
+
+    #[test]
+    fn read_invalid_binary_digit() {
+        let mut lex = Lexer::new("0b2".into());
+
+        assert_eq!(
+            lex.next_token(),
+            Token::error("Invalid digit '2' in base 2 literal")
+        );
+    }
+
+    #[test]
+    fn read_invalid_octal_digit() {
+        let mut lex = Lexer::new("0o78".into());
+
+        assert_eq!(
+            lex.next_token(),
+            Token::error("Invalid digit '8' in base 8 literal")
+        );
+    }
+
+    #[test]
+    fn read_empty_hex_literal() {
+        let mut lex = Lexer::new("0x;".into());
+
+        assert_eq!(
+            lex.next_token(),
+            Token::error("Missing digits in base 16 literal")
+        );
+    }
+
     #[test]
     fn get_next_token() {
-        let input = "=+(){},;!===";
+        let input = "=+(){},;!= ==";
         let mut lexer = Lexer::new(input.into());
 
         let tokens = vec![
@@ -304,13 +795,307 @@ mod test {
         }
     }
 
+    #[test]
+    fn read_percent() {
+        let mut lex = Lexer::new("7 % 3;".into());
+
+        assert_eq!(lex.next_token(), Token::number("7"));
+        assert_eq!(lex.next_token(), Token::Percent);
+        assert_eq!(lex.next_token(), Token::number("3"));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+    }
+
+    #[test]
+    fn read_star_star() {
+        let mut lex = Lexer::new("2 ** 3 * 4".into());
+
+        assert_eq!(lex.next_token(), Token::Number("2".into()));
+        assert_eq!(lex.next_token(), Token::StarStar);
+        assert_eq!(lex.next_token(), Token::Number("3".into()));
+        assert_eq!(lex.next_token(), Token::Asterisk);
+        assert_eq!(lex.next_token(), Token::Number("4".into()));
+    }
+
+    #[test]
+    fn read_bitwise_operators() {
+        let mut lex = Lexer::new("a & b | c ^ ~d << 1 >> 2 && e || f <= g >= h".into());
+
+        let tokens = vec![
+            Token::ident("a"),
+            Token::Ampersand,
+            Token::ident("b"),
+            Token::Pipe,
+            Token::ident("c"),
+            Token::Caret,
+            Token::Tilde,
+            Token::ident("d"),
+            Token::ShiftLeft,
+            Token::number("1"),
+            Token::ShiftRight,
+            Token::number("2"),
+            Token::And,
+            Token::ident("e"),
+            Token::Or,
+            Token::ident("f"),
+            Token::LessThanOrEqual,
+            Token::ident("g"),
+            Token::GreaterThanOrEqual,
+            Token::ident("h"),
+        ];
+
+        for token in tokens {
+            assert_eq!(lex.next_token(), token);
+        }
+    }
+
+    #[test]
+    fn read_update_operators() {
+        let mut lex = Lexer::new("i++ + --j - -k".into());
+
+        let tokens = vec![
+            Token::ident("i"),
+            Token::PlusPlus,
+            Token::Plus,
+            Token::MinusMinus,
+            Token::ident("j"),
+            Token::Minus,
+            Token::Minus,
+            Token::ident("k"),
+        ];
+
+        for token in tokens {
+            assert_eq!(lex.next_token(), token);
+        }
+    }
+
+    #[test]
+    fn read_strict_equality() {
+        let mut lex = Lexer::new("a === b !== c == d != e".into());
+
+        let tokens = vec![
+            Token::ident("a"),
+            Token::StrictEqual,
+            Token::ident("b"),
+            Token::StrictNotEqual,
+            Token::ident("c"),
+            Token::Equal,
+            Token::ident("d"),
+            Token::NotEqual,
+            Token::ident("e"),
+        ];
+
+        for token in tokens {
+            assert_eq!(lex.next_token(), token);
+        }
+    }
+
+    #[test]
+    fn read_two_char_operators_at_end_of_input() {
+        let cases = vec![
+            ("a ==", Token::Equal),
+            ("a <=", Token::LessThanOrEqual),
+            ("a >=", Token::GreaterThanOrEqual),
+            ("a !=", Token::NotEqual),
+            ("a &&", Token::And),
+            ("a ||", Token::Or),
+        ];
+
+        for (input, operator) in cases {
+            let mut lex = Lexer::new(input.into());
+
+            assert_eq!(lex.next_token(), Token::ident("a"));
+            assert_eq!(lex.next_token(), operator);
+            assert_eq!(lex.next_token(), Token::Eof);
+        }
+    }
+
+    #[test]
+    fn read_operator_prefixes_at_end_of_input() {
+        let cases = vec![
+            ("=", Token::Assign),
+            ("<", Token::LessThan),
+            (">", Token::GreaterThan),
+            ("!", Token::Bang),
+        ];
+
+        for (input, operator) in cases {
+            let mut lex = Lexer::new(input.into());
+
+            assert_eq!(lex.next_token(), operator);
+            assert_eq!(lex.next_token(), Token::Eof);
+        }
+    }
+
+    #[test]
+    fn read_ternary() {
+        let mut lex = Lexer::new("a ? b : c;".into());
+
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::Question);
+        assert_eq!(lex.next_token(), Token::ident("b"));
+        assert_eq!(lex.next_token(), Token::Colon);
+        assert_eq!(lex.next_token(), Token::ident("c"));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+    }
+
+    #[test]
+    fn read_nullish() {
+        let mut lex = Lexer::new("a ?? b ? c : d".into());
+
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::QuestionQuestion);
+        assert_eq!(lex.next_token(), Token::ident("b"));
+        assert_eq!(lex.next_token(), Token::Question);
+        assert_eq!(lex.next_token(), Token::ident("c"));
+        assert_eq!(lex.next_token(), Token::Colon);
+        assert_eq!(lex.next_token(), Token::ident("d"));
+    }
+
+    #[test]
+    fn read_loop_control_keywords() {
+        let mut lex = Lexer::new("break; continue; breaks".into());
+
+        assert_eq!(lex.next_token(), Token::Break);
+        assert_eq!(lex.next_token(), Token::Semicolon);
+        assert_eq!(lex.next_token(), Token::Continue);
+        assert_eq!(lex.next_token(), Token::Semicolon);
+        assert_eq!(lex.next_token(), Token::ident("breaks"));
+    }
+
+    #[test]
+    fn read_ident_with_digits() {
+        let mut lex = Lexer::new("foo1 _bar2 a1b2 1abc".into());
+
+        assert_eq!(lex.next_token(), Token::ident("foo1"));
+        assert_eq!(lex.next_token(), Token::ident("_bar2"));
+        assert_eq!(lex.next_token(), Token::ident("a1b2"));
+        assert_eq!(lex.next_token(), Token::Number("1".into()));
+        assert_eq!(lex.next_token(), Token::ident("abc"));
+        assert_eq!(lex.next_token(), Token::Eof);
+    }
+
+    #[test]
+    fn read_typeof() {
+        let mut lex = Lexer::new("typeof a".into());
+
+        assert_eq!(lex.next_token(), Token::Typeof);
+        assert_eq!(lex.next_token(), Token::ident("a"));
+    }
+
+    #[test]
+    fn read_this() {
+        let mut lex = Lexer::new("this.name".into());
+
+        assert_eq!(lex.next_token(), Token::This);
+        assert_eq!(lex.next_token(), Token::Dot);
+        assert_eq!(lex.next_token(), Token::ident("name"));
+    }
+
+    #[test]
+    fn read_ellipsis() {
+        let mut lex = Lexer::new("[...a, b.c]".into());
+
+        assert_eq!(lex.next_token(), Token::LBracket);
+        assert_eq!(lex.next_token(), Token::Ellipsis);
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::Comma);
+        assert_eq!(lex.next_token(), Token::ident("b"));
+        assert_eq!(lex.next_token(), Token::Dot);
+        assert_eq!(lex.next_token(), Token::ident("c"));
+        assert_eq!(lex.next_token(), Token::RBracket);
+    }
+
+    #[test]
+    fn read_in() {
+        let mut lex = Lexer::new("a in b index".into());
+
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::In);
+        assert_eq!(lex.next_token(), Token::ident("b"));
+        assert_eq!(lex.next_token(), Token::ident("index"));
+    }
+
+    #[test]
+    fn read_switch_keywords() {
+        let mut lex = Lexer::new("switch case default:".into());
+
+        assert_eq!(lex.next_token(), Token::Switch);
+        assert_eq!(lex.next_token(), Token::Case);
+        assert_eq!(lex.next_token(), Token::Default);
+        assert_eq!(lex.next_token(), Token::Colon);
+    }
+
+    #[test]
+    fn read_exception_keywords() {
+        let mut lex = Lexer::new("try catch finally throw".into());
+
+        assert_eq!(lex.next_token(), Token::Try);
+        assert_eq!(lex.next_token(), Token::Catch);
+        assert_eq!(lex.next_token(), Token::Finally);
+        assert_eq!(lex.next_token(), Token::Throw);
+    }
+
+    #[test]
+    fn read_const() {
+        let mut lex = Lexer::new("const a = 1; constant".into());
+
+        assert_eq!(lex.next_token(), Token::Const);
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::Assign);
+        assert_eq!(lex.next_token(), Token::Number("1".into()));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+        assert_eq!(lex.next_token(), Token::ident("constant"));
+    }
+
+    #[test]
+    fn read_brackets() {
+        let mut lex = Lexer::new("[1, [2]]".into());
+
+        let tokens = vec![
+            Token::LBracket,
+            Token::number("1"),
+            Token::Comma,
+            Token::LBracket,
+            Token::number("2"),
+            Token::RBracket,
+            Token::RBracket,
+            Token::Eof,
+        ];
+
+        for token in tokens {
+            assert_eq!(token, lex.next_token());
+        }
+    }
+
+    #[test]
+    fn read_dot() {
+        let mut lex = Lexer::new("a.b .5 c.d.e".into());
+
+        let tokens = vec![
+            Token::ident("a"),
+            Token::Dot,
+            Token::ident("b"),
+            Token::number(".5"),
+            Token::ident("c"),
+            Token::Dot,
+            Token::ident("d"),
+            Token::Dot,
+            Token::ident("e"),
+            Token::Eof,
+        ];
+
+        for token in tokens {
+            assert_eq!(token, lex.next_token());
+        }
+    }
+
     #[test]
     fn get_next_complete() {
         let input = r#"let add = function(x, y) {
                 return x + y;
             };
             let result = add(five, ten);
-            !-/*5;
+            !-/ *5;
             5 < 10 > 5;
             if (5 < 10) {
                 return true;
@@ -417,6 +1202,178 @@ mod test {
         }
     }
 
+    #[test]
+    fn block_comment() {
+        let input = r#"let a = 1;
+            /*
+             * this is ignored
+             * let b = 2;
+             */
+            let c = a;"#;
+        let mut lex = Lexer::new(input.into());
+
+        let tokens = vec![
+            Token::Let,
+            Token::ident("a"),
+            Token::Assign,
+            Token::number("1"),
+            Token::Semicolon,
+            Token::Let,
+            Token::ident("c"),
+            Token::Assign,
+            Token::ident("a"),
+            Token::Semicolon,
+            Token::Eof,
+        ];
+
+        for token in tokens {
+            assert_eq!(token, lex.next_token());
+        }
+    }
+
+    #[test]
+    fn inline_block_comment() {
+        let input = "1 /* one */ + /**/ 2";
+        let mut lex = Lexer::new(input.into());
+
+        assert_eq!(lex.next_token(), Token::number("1"));
+        assert_eq!(lex.peek_token(), Token::Plus);
+        assert_eq!(lex.next_token(), Token::Plus);
+        assert_eq!(lex.next_token(), Token::number("2"));
+        assert_eq!(lex.next_token(), Token::Eof);
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let mut lex = Lexer::new("let a; /* never closed".into());
+
+        assert_eq!(lex.next_token(), Token::Let);
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+        assert_eq!(lex.next_token(), Token::error("Unterminated block comment"));
+        assert_eq!(lex.position(), (1, 8));
+        assert_eq!(lex.next_token(), Token::Eof);
+    }
+
+    #[test]
+    fn nested_block_comment() {
+        let mut lex = Lexer::new("/* outer /* inner */ */".into());
+
+        assert_eq!(
+            lex.next_token(),
+            Token::error("Nested block comments are not supported")
+        );
+    }
+
+    #[test]
+    fn unexpected_character() {
+        let mut lex = Lexer::new("a # b".into());
+
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::error("Unexpected character '#'"));
+        assert_eq!(lex.position(), (1, 3));
+        assert_eq!(lex.next_token(), Token::ident("b"));
+    }
+
+    #[test]
+    fn position() {
+        let input = "let a = 1;\n\n  let bb = \"two\";\n    a;";
+        let mut lex = Lexer::new(input.into());
+
+        assert_eq!(lex.next_token(), Token::Let);
+        assert_eq!(lex.position(), (1, 1));
+
+        for _ in 0..4 {
+            lex.next_token();
+        }
+
+        assert_eq!(lex.next_token(), Token::Let);
+        assert_eq!(lex.position(), (3, 3));
+        assert_eq!(lex.next_token(), Token::ident("bb"));
+        assert_eq!(lex.position(), (3, 7));
+
+        assert_eq!(lex.peek_token(), Token::Assign);
+        assert_eq!(lex.position(), (3, 7));
+
+        assert_eq!(lex.next_token(), Token::Assign);
+        assert_eq!(lex.next_token(), Token::string("two"));
+        assert_eq!(lex.position(), (3, 12));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.position(), (4, 5));
+    }
+
+    #[test]
+    fn newlines_are_whitespace() {
+        let mut lex = Lexer::new("a\n+\r\n\rb\n".into());
+
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::Plus);
+        assert_eq!(lex.next_token(), Token::ident("b"));
+        assert_eq!(lex.next_token(), Token::Eof);
+    }
+
+    #[test]
+    fn newline_before_tokens() {
+        let mut lex = Lexer::new("a b\nc /* \n */ d /* */\r\ne".into());
+
+        for (ident, newline) in [("a", false), ("b", false), ("c", true), ("d", true)] {
+            assert_eq!(lex.newline_before_next(), newline);
+            assert_eq!(lex.next_token(), Token::ident(ident));
+            assert_eq!(lex.newline_before(), newline);
+        }
+
+        assert_eq!(lex.next_token(), Token::ident("e"));
+        assert!(lex.newline_before());
+    }
+
+    #[test]
+    fn tokenize_all() {
+        assert_eq!(
+            Lexer::tokenize_all("x \"héllo\""),
+            vec![(Token::ident("x"), 0, 1), (Token::string("héllo"), 2, 10)]
+        );
+        assert_eq!(
+            Lexer::tokenize_all("/* c */ 0x1F>=\n"),
+            vec![
+                (Token::number("0x1F"), 8, 12),
+                (Token::GreaterThanOrEqual, 12, 14)
+            ]
+        );
+        assert_eq!(Lexer::tokenize_all("  "), vec![]);
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let mut lex = Lexer::new("let a = 1;\r\n".into());
+
+        assert_eq!(lex.next_token(), Token::Let);
+        assert_eq!(lex.next_token(), Token::ident("a"));
+        assert_eq!(lex.next_token(), Token::Assign);
+        assert_eq!(lex.next_token(), Token::Number("1".into()));
+        assert_eq!(lex.next_token(), Token::Semicolon);
+        assert_eq!(lex.next_token(), Token::Eof);
+    }
+
+    #[test]
+    fn position_with_mixed_line_endings() {
+        let mut lex = Lexer::new("a\r\nb\rc\nd".into());
+
+        for (ident, position) in [("a", (1, 1)), ("b", (2, 1)), ("c", (3, 1)), ("d", (4, 1))] {
+            assert_eq!(lex.next_token(), Token::ident(ident));
+            assert_eq!(lex.position(), position);
+        }
+    }
+
+    #[test]
+    fn position_after_block_comment() {
+        let input = "/* one\ntwo */ x";
+        let mut lex = Lexer::new(input.into());
+
+        assert_eq!(lex.next_token(), Token::ident("x"));
+        assert_eq!(lex.position(), (2, 8));
+    }
+
     #[test]
     fn peek_token() {
         let input = "let five = 5;";
@@ -430,13 +1387,32 @@ mod test {
         assert_eq!(Token::Let, lex.curr_token());
     }
 
+    #[test]
+    fn iterate_tokens() {
+        let lex = Lexer::new("let a = 1;".into());
+
+        let tokens = lex.collect::<Vec<Token>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Let,
+                Token::ident("a"),
+                Token::Assign,
+                Token::number("1"),
+                Token::Semicolon,
+                Token::Eof,
+            ]
+        );
+    }
+
     #[test]
     fn match_token() {
         let input = "let five = 5;";
 
         let mut lex = Lexer::new(input.into());
 
-        assert_eq!(lex.match_token_and_consume(Token::Let), true);
-        assert_eq!(lex.match_token_and_consume(Token::Let), false);
+        assert!(lex.match_token_and_consume(Token::Let));
+        assert!(!lex.match_token_and_consume(Token::Let));
     }
 }
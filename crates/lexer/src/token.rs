@@ -3,41 +3,73 @@ pub enum Token {
     Ident(String),
     Number(String),
     String(String),
-    Print, // temporary
     Null,
     Illegal,
+    /// A malformed piece of input, carrying a description of what is wrong with it
+    Error(String),
     Eof,
     Bang,
     Assign,
     Equal,
     NotEqual,
+    StrictEqual,
+    StrictNotEqual,
     LessThan,
     LessThanOrEqual,
     GreaterThan,
     GreaterThanOrEqual,
     Plus,
     Minus,
+    PlusPlus,
+    MinusMinus,
     Asterisk,
+    StarStar,
+    Percent,
     And,
     Or,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
     ForwardSlash,
     Comma,
+    Dot,
+    Ellipsis,
     Semicolon,
+    Question,
+    QuestionQuestion,
+    Colon,
     Lparen,
     Rparen,
     LSquirly,
     RSquirly,
+    LBracket,
+    RBracket,
     Function,
     Let,
+    Const,
     If,
     Else,
     While,
     For,
     Do,
     Return,
+    Break,
+    Continue,
+    Switch,
+    Case,
+    Default,
+    Try,
+    Catch,
+    Finally,
+    Throw,
+    Typeof,
+    This,
+    In,
     True,
     False,
-    Newline,
 }
 
 impl Token {
@@ -52,4 +84,8 @@ impl Token {
     pub fn ident<S: Into<String>>(ident: S) -> Self {
         Token::Ident(ident.into())
     }
+
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Token::Error(message.into())
+    }
 }
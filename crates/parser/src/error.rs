@@ -0,0 +1,54 @@
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    Syntax,
+    /// The input ended before the statement it was in did, more input could make it valid
+    UnexpectedEof,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(message: S, line: usize, column: usize) -> Self {
+        ParseError {
+            message: message.into(),
+            line,
+            column,
+            kind: ParseErrorKind::Syntax,
+        }
+    }
+
+    pub fn unexpected_eof<S: Into<String>>(message: S, line: usize, column: usize) -> Self {
+        ParseError {
+            kind: ParseErrorKind::UnexpectedEof,
+            ..ParseError::new(message, line, column)
+        }
+    }
+
+    /**
+     * Whether parsing failed only because the input stopped early, a REPL can read another line
+     * and try again instead of reporting the error
+     */
+    pub fn is_unexpected_eof(&self) -> bool {
+        return self.kind == ParseErrorKind::UnexpectedEof;
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
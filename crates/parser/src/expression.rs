@@ -1,13 +1,31 @@
-use crate::{ident::Ident, operator::Operator, value::ParserValue};
+use crate::{ident::Ident, operator::Operator, printer::Printer, value::ParserValue};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Expression {
-    Variable(Ident),
+    /// `depth` is how many scopes separate the use from its declaration, `None` for globals. It
+    /// is filled in by the interpreter's resolver, the parser always leaves it empty
+    Variable {
+        ident: Ident,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        depth: Option<usize>,
+    },
+    /// The object a function was called on, `depth` works like a variable's
+    This {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        depth: Option<usize>,
+    },
     Grouping(Box<Expression>),
     Literal(ParserValue),
+    Array(Vec<Expression>),
+    Object(Vec<Property>),
+    /// `...value` inside an array literal, expands the array in place
+    Spread(Box<Expression>),
     Assignement {
         ident: Ident,
         value: Box<Expression>,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        depth: Option<usize>,
     },
     Unary {
         operator: Operator,
@@ -18,10 +36,48 @@ pub enum Expression {
         operator: Operator,
         right: Box<Expression>,
     },
+    Logical {
+        left: Box<Expression>,
+        operator: Operator,
+        right: Box<Expression>,
+    },
     Call {
         callee: Box<Expression>,
         arguments: Vec<Expression>,
     },
+    Get {
+        object: Box<Expression>,
+        name: Ident,
+    },
+    Set {
+        object: Box<Expression>,
+        name: Ident,
+        value: Box<Expression>,
+    },
+    Index {
+        object: Box<Expression>,
+        index: Box<Expression>,
+    },
+    Ternary {
+        condition: Box<Expression>,
+        then_branch: Box<Expression>,
+        else_branch: Box<Expression>,
+    },
+    /// `++x`, `x--` and the like, `target` is a variable, a property or an array element
+    Update {
+        operator: Operator,
+        prefix: bool,
+        target: Box<Expression>,
+    },
+}
+
+/// An entry of an object literal
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Property {
+    Named(String, Expression),
+    /// `...value`, copies every property of the object in place
+    Spread(Expression),
 }
 
 impl Expression {
@@ -33,6 +89,23 @@ impl Expression {
         Expression::Literal(value)
     }
 
+    pub fn array(elements: Vec<Expression>) -> Expression {
+        Expression::Array(elements)
+    }
+
+    pub fn object(properties: Vec<(String, Expression)>) -> Expression {
+        Expression::Object(
+            properties
+                .into_iter()
+                .map(|(key, value)| Property::Named(key, value))
+                .collect(),
+        )
+    }
+
+    pub fn spread(expression: Expression) -> Expression {
+        Expression::Spread(Box::new(expression))
+    }
+
     pub fn call(callee: Expression, arguments: Vec<Expression>) -> Expression {
         Expression::Call {
             callee: Box::new(callee),
@@ -55,15 +128,84 @@ impl Expression {
         }
     }
 
+    pub fn get(object: Expression, name: Ident) -> Expression {
+        Expression::Get {
+            object: Box::new(object),
+            name,
+        }
+    }
+
+    pub fn set(object: Expression, name: Ident, value: Expression) -> Expression {
+        Expression::Set {
+            object: Box::new(object),
+            name,
+            value: Box::new(value),
+        }
+    }
+
+    pub fn index(object: Expression, index: Expression) -> Expression {
+        Expression::Index {
+            object: Box::new(object),
+            index: Box::new(index),
+        }
+    }
+
+    pub fn ternary(
+        condition: Expression,
+        then_branch: Expression,
+        else_branch: Expression,
+    ) -> Expression {
+        Expression::Ternary {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        }
+    }
+
+    pub fn logical(left: Expression, operator: Operator, right: Expression) -> Expression {
+        Expression::Logical {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }
+    }
+
+    pub fn update(operator: Operator, prefix: bool, target: Expression) -> Expression {
+        Expression::Update {
+            operator,
+            prefix,
+            target: Box::new(target),
+        }
+    }
+
     pub fn assignement(ident: Ident, value: Expression) -> Expression {
         Expression::Assignement {
             ident,
             value: Box::new(value),
+            depth: None,
         }
     }
 
     pub fn variable<S: Into<String>>(ident: S) -> Expression {
-        Expression::Variable(Ident::new(ident.into()))
+        Expression::Variable {
+            ident: Ident::new(ident.into()),
+            depth: None,
+        }
+    }
+
+    pub fn this() -> Expression {
+        Expression::This { depth: None }
+    }
+
+    /**
+     * Source code that parses back to this expression
+     */
+    pub fn to_source(&self) -> String {
+        let mut printer = Printer::new();
+
+        printer.expression(self);
+
+        return printer.finish();
     }
 }
 
@@ -1,4 +1,5 @@
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Ident(String);
 
 impl Ident {
@@ -11,8 +12,8 @@ impl Ident {
     }
 }
 
-impl Into<String> for Ident {
-    fn into(self) -> String {
-        self.0
+impl From<Ident> for String {
+    fn from(ident: Ident) -> Self {
+        ident.0
     }
 }
@@ -1,7 +1,9 @@
+pub mod error;
 pub mod expression;
 pub mod ident;
 mod macros;
 pub mod operator;
 pub mod parser;
+pub mod printer;
 pub mod statements;
 pub mod value;
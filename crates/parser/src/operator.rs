@@ -1,18 +1,35 @@
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Operator {
     Plus,
     Minus,
+    UnaryPlus,
     Asterisk,
     Slash,
+    Modulo,
+    Power,
+    Increment,
+    Decrement,
     Equal,
     Bang,
+    Typeof,
     NotEqual,
+    StrictEqual,
+    StrictNotEqual,
     LogicalAnd,
     LogicalOr,
     And,
     Or,
+    Nullish,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
     LessThan,
     LessThanOrEqual,
     GreaterThan,
     GreaterThanOrEqual,
+    In,
 }
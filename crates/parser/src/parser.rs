@@ -1,7 +1,14 @@
 use lexer::{lexer::Lexer, token::Token};
 
 use crate::{
-    expression::Expression, ident::Ident, operator::Operator, statements::statement::Statement,
+    error::ParseError,
+    expression::{Expression, Property},
+    ident::Ident,
+    operator::Operator,
+    statements::{
+        block::BlockStatement, r#let::Pattern, r#try::CatchClause, statement::Statement,
+        switch::SwitchCase,
+    },
     value::ParserValue,
 };
 
@@ -10,104 +17,251 @@ enum FunctionType {
     // Method,
 }
 
+/**
+ * How deeply statements and expressions may nest, parsing recurses once per level so input nested
+ * deeper than this could overflow the stack
+ */
+pub const MAX_NESTING_DEPTH: usize = 64;
+
 pub struct Parser {
     lexer: Lexer,
+    /// How many nested statements and expressions are being parsed
+    depth: usize,
+    loop_depth: usize,
+    switch_depth: usize,
+    /// Labels of the enclosing statements, and whether each one labels a loop
+    labels: Vec<(String, bool)>,
 }
 
 impl Parser {
     pub fn new<S: Into<String>>(input: S) -> Parser {
         Parser {
             lexer: Lexer::new(input.into()),
+            depth: 0,
+            loop_depth: 0,
+            switch_depth: 0,
+            labels: Vec::new(),
         }
     }
 
     /**
      * parse -> declaration* EOF ;
      */
-    pub fn parse(&mut self) -> Vec<Statement> {
+    pub fn parse(&mut self) -> Result<Vec<Statement>, ParseError> {
         let mut statements = Vec::new();
 
-        while !self.lexer.is_at_end() {
-            statements.push(self.declaration());
+        while self.lexer.peek_token() != Token::Eof {
+            if self.empty_statement() {
+                continue;
+            }
+
+            statements.push(self.declaration()?);
             self.lexer.match_token_and_consume(Token::Semicolon);
         }
 
-        return statements;
+        return Ok(statements);
     }
 
     /**
-     * varDecl -> "let" IDENTIFIER ( "=" expression )? ";" ;
+     * Whether `source` forms whole statements, false while a brace, parenthesis or expression is
+     * still open. Input with any other syntax error counts as complete, more lines can't fix it
      */
-    fn var_decl(&mut self) -> Statement {
-        let ident = self.parse_ident();
-        let mut expr = None;
+    pub fn is_complete(source: &str) -> bool {
+        return match Parser::new(source).parse() {
+            Err(error) => !error.is_unexpected_eof(),
+            Ok(_) => true,
+        };
+    }
 
-        if self.lexer.match_token_and_consume(Token::Assign) {
-            expr = Some(self.expression());
+    /**
+     * Parses the whole input as a single expression, for tools that evaluate expressions rather
+     * than programs
+     */
+    pub fn parse_expression(&mut self) -> Result<Expression, ParseError> {
+        let expression = self.expression()?;
+
+        if self.lexer.peek_token() != Token::Eof {
+            return Err(self.error("Expected the end of the expression"));
+        }
+
+        return Ok(expression);
+    }
+
+    /**
+     * Parses `source` and serializes the resulting statements to JSON, for tools built on top of
+     * the parser
+     */
+    #[cfg(feature = "serde")]
+    pub fn parse_to_json(source: &str) -> Result<String, ParseError> {
+        let statements = Parser::new(source).parse()?;
+
+        return Ok(serde_json::to_string(&statements).expect("The AST is always serializable"));
+    }
+
+    /**
+     * varDecl -> "let" pattern ( "=" expression )? ( "," pattern ( "=" expression )? )* ";" ;
+     */
+    fn var_decl(&mut self) -> Result<Statement, ParseError> {
+        let declaration = self.var_declarations()?;
+
+        self.terminator()?;
+
+        return Ok(declaration);
+    }
+
+    /**
+     * The declarations of a varDecl, without its terminator
+     */
+    fn var_declarations(&mut self) -> Result<Statement, ParseError> {
+        let mut declarations = Vec::new();
+
+        loop {
+            let pattern = self.pattern()?;
+            let mut expr = None;
+
+            if self.lexer.match_token_and_consume(Token::Assign) {
+                expr = Some(self.expression()?);
+            } else if !matches!(pattern, Pattern::Ident(_)) {
+                return Err(self.error("Expected an initializer for destructuring declaration"));
+            }
+
+            declarations.push((pattern, expr));
+
+            if !self.lexer.match_token_and_consume(Token::Comma) {
+                break;
+            }
+        }
+
+        return Ok(Statement::_let_declarations(declarations));
+    }
+
+    /**
+     * constDecl -> "const" pattern "=" expression ( "," pattern "=" expression )* ";" ;
+     */
+    fn const_decl(&mut self) -> Result<Statement, ParseError> {
+        let mut declarations = Vec::new();
+
+        loop {
+            let pattern = self.pattern()?;
+
+            self.expect(
+                Token::Assign,
+                "Expected an initializer for const declaration",
+            )?;
+
+            declarations.push((pattern, self.expression()?));
+
+            if !self.lexer.match_token_and_consume(Token::Comma) {
+                break;
+            }
+        }
+
+        self.terminator()?;
+
+        return Ok(Statement::_const_declarations(declarations));
+    }
+
+    /**
+     * pattern -> IDENTIFIER | "[" identifiers? "]" | "{" identifiers? "}" ;
+     * identifiers -> IDENTIFIER ( "," IDENTIFIER )* ","? ;
+     */
+    fn pattern(&mut self) -> Result<Pattern, ParseError> {
+        let closing = match self.lexer.peek_token() {
+            Token::LBracket => Token::RBracket,
+            Token::LSquirly => Token::RSquirly,
+            _ => return Ok(Pattern::Ident(self.parse_ident()?)),
+        };
+
+        self.lexer.next_token();
+
+        let mut idents = Vec::new();
+
+        if self.lexer.peek_token() != closing {
+            loop {
+                idents.push(self.parse_ident()?);
+
+                if !self.list_continues(closing.clone()) {
+                    break;
+                }
+            }
+        }
+
+        if closing == Token::RBracket {
+            self.expect(Token::RBracket, "Expected a closing bracket")?;
+
+            return Ok(Pattern::Array(idents));
         }
 
-        self.lexer.match_token_and_consume(Token::Semicolon);
+        self.expect(Token::RSquirly, "Expected a closing brace")?;
 
-        return Statement::_let(ident, expr);
+        return Ok(Pattern::Object(idents));
     }
 
     /**
-     * function -> "(" parameters? ")" block ;
+     * function -> IDENTIFIER? "(" parameters? ")" block ;
      */
-    fn function(&mut self) -> Expression {
-        self.expect(Token::Lparen, "Expected a left parenthesis");
+    fn function(&mut self) -> Result<Expression, ParseError> {
+        let ident = match self.lexer.peek_token() {
+            Token::Ident(_) => Some(self.parse_ident()?),
+            _ => None,
+        };
+
+        let (params, body) = self.function_parts()?;
+
+        return Ok(Expression::literal(ParserValue::function(
+            ident, params, body,
+        )));
+    }
+
+    fn function_parts(&mut self) -> Result<(Vec<Ident>, BlockStatement), ParseError> {
+        self.expect(Token::Lparen, "Expected a left parenthesis")?;
 
         let mut params = Vec::new();
 
         if self.lexer.peek_token() != Token::Rparen {
             loop {
                 if params.len() >= 255 {
-                    panic!("Cannot have more than 255 parameters");
+                    return Err(self.error("Cannot have more than 255 parameters"));
                 }
 
-                params.push(self.parse_ident());
+                params.push(self.parse_ident()?);
 
-                if !self.lexer.match_token_and_consume(Token::Comma) {
+                if !self.list_continues(Token::Rparen) {
                     break;
                 }
             }
         }
 
-        self.expect(Token::Rparen, "Expected a right parenthesis");
-        self.expect(Token::LSquirly, "Expected a left brace");
+        self.expect(Token::Rparen, "Expected a right parenthesis")?;
+        self.expect(Token::LSquirly, "Expected a left brace")?;
 
-        let body = if let Statement::Block(block) = self.block_statement() {
-            block
-        } else {
-            panic!("Expected a block statement");
-        };
+        // loops enclosing the function don't make break/continue valid inside its body
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let switch_depth = std::mem::take(&mut self.switch_depth);
+        let labels = std::mem::take(&mut self.labels);
+        let body = self.block();
+        self.loop_depth = loop_depth;
+        self.switch_depth = switch_depth;
+        self.labels = labels;
 
-        return Expression::literal(ParserValue::function(None, params, body));
+        return Ok((params, body?));
     }
 
     /**
      * functionDecl -> "function" IDENTIFIER function ;
-     * parameters -> IDENTIFIER ( "," IDENTIFIER )* ;
+     * parameters -> IDENTIFIER ( "," IDENTIFIER )* ","? ;
      */
-    fn function_decl(&mut self, _fn_type: FunctionType) -> Statement {
-        let ident = self.parse_ident();
-
-        if let Expression::Literal(ParserValue::Function {
-            ident: _,
-            params,
-            body,
-        }) = self.function()
-        {
-            return Statement::function(ident, params, body);
-        }
+    fn function_decl(&mut self, _fn_type: FunctionType) -> Result<Statement, ParseError> {
+        let ident = self.parse_ident()?;
+        let (params, body) = self.function_parts()?;
 
-        panic!("Expected a block statement");
+        return Ok(Statement::function(ident, params, body));
     }
 
     /**
-     * declaration -> functionDecl | varDecl | statement ;
+     * declaration -> functionDecl | varDecl | constDecl | statement ;
      */
-    fn declaration(&mut self) -> Statement {
+    fn declaration(&mut self) -> Result<Statement, ParseError> {
         if self.lexer.match_token_and_consume(Token::Function) {
             return self.function_decl(FunctionType::Function);
         }
@@ -116,140 +270,391 @@ impl Parser {
             return self.var_decl();
         }
 
+        if self.lexer.match_token_and_consume(Token::Const) {
+            return self.const_decl();
+        }
+
         return self.statement();
     }
 
     /**
      * block -> "{" declaration* "}" ;
      */
-    fn block_statement(&mut self) -> Statement {
+    fn block(&mut self) -> Result<BlockStatement, ParseError> {
         let mut statements = Vec::new();
 
         while self.lexer.peek_token() != Token::RSquirly && self.lexer.peek_token() != Token::Eof {
-            statements.push(self.declaration());
+            if self.empty_statement() {
+                continue;
+            }
+
+            statements.push(self.declaration()?);
             self.lexer.match_token_and_consume(Token::Semicolon);
         }
 
-        self.expect(Token::RSquirly, "Expected a right brace");
+        self.expect(Token::RSquirly, "Expected a right brace")?;
+
+        return Ok(BlockStatement::new(statements));
+    }
+
+    /**
+     * Consumes a lone semicolon, an empty statement. Statement lists leave them out entirely
+     */
+    fn empty_statement(&mut self) -> bool {
+        return self.lexer.match_token_and_consume(Token::Semicolon);
+    }
 
-        return Statement::_block(statements);
+    fn block_statement(&mut self) -> Result<Statement, ParseError> {
+        return Ok(Statement::Block(self.block()?));
     }
 
     /**
      * if -> "if" "(" expression ")" statement ( "else" statement )? ;
+     *
+     * An `else` goes with the closest `if` that doesn't have one yet, since the inner `if` is parsed
+     * first and takes it
      */
-    fn if_statement(&mut self) -> Statement {
-        self.expect(Token::Lparen, "Expected a left parenthesis");
+    fn if_statement(&mut self) -> Result<Statement, ParseError> {
+        self.expect(Token::Lparen, "Expected a left parenthesis")?;
 
-        let condition = self.expression();
+        let condition = self.expression()?;
 
-        self.expect(Token::Rparen, "Expected a right parenthesis");
+        self.expect(Token::Rparen, "Expected a right parenthesis")?;
 
-        let consequence = self.statement();
+        let consequence = self.statement()?;
 
         let alternative = if self.lexer.match_token_and_consume(Token::Else) {
-            Some(self.statement())
+            Some(self.statement()?)
         } else {
             None
         };
 
-        return Statement::_if(condition, consequence, alternative);
+        return Ok(Statement::_if(condition, consequence, alternative));
     }
 
-    fn expression_statement(&mut self) -> Statement {
-        let expression = self.expression();
+    fn expression_statement(&mut self) -> Result<Statement, ParseError> {
+        let expression = self.expression()?;
+
+        // an identifier followed by a colon can only start a labeled statement
+        if let Expression::Variable { ident, .. } = &expression {
+            if self.lexer.match_token_and_consume(Token::Colon) {
+                return self.labeled_statement(ident.clone());
+            }
+        }
+
+        self.terminator()?;
 
-        return Statement::_expression(expression);
+        return Ok(Statement::_expression(expression));
     }
 
     /**
-     * while -> "while" "(" expression ")" statement ;
+     * labeled -> IDENTIFIER ":" statement ;
      */
-    fn while_statement(&mut self) -> Statement {
-        self.expect(Token::Lparen, "Expected a left parenthesis");
+    fn labeled_statement(&mut self, label: Ident) -> Result<Statement, ParseError> {
+        let name = label.value();
 
-        let condition = self.expression();
+        if self.labels.iter().any(|(enclosing, _)| *enclosing == name) {
+            return Err(self.error_at(format!("Label '{}' has already been declared", name)));
+        }
 
-        self.expect(Token::Rparen, "Expected a right parenthesis");
+        let is_loop = matches!(
+            self.lexer.peek_token(),
+            Token::While | Token::Do | Token::For
+        );
 
+        self.labels.push((name, is_loop));
         let body = self.statement();
+        self.labels.pop();
+
+        return Ok(Statement::_labeled(label, body?));
+    }
+
+    /**
+     * while -> "while" "(" expression ")" statement ;
+     */
+    fn while_statement(&mut self) -> Result<Statement, ParseError> {
+        self.expect(Token::Lparen, "Expected a left parenthesis")?;
+
+        let condition = self.expression()?;
+
+        self.expect(Token::Rparen, "Expected a right parenthesis")?;
+
+        let body = self.loop_body()?;
+
+        return Ok(Statement::_while(condition, body));
+    }
+
+    /**
+     * doWhile -> "do" statement "while" "(" expression ")" ";" ;
+     */
+    fn do_while_statement(&mut self) -> Result<Statement, ParseError> {
+        let body = self.loop_body()?;
+
+        self.expect(Token::While, "Expected 'while' after do-while body")?;
+        self.expect(Token::Lparen, "Expected a left parenthesis")?;
+
+        let condition = self.expression()?;
+
+        self.expect(Token::Rparen, "Expected a right parenthesis")?;
+        self.terminator()?;
+
+        return Ok(Statement::_do_while(body, condition));
+    }
+
+    /**
+     * try -> "try" block ( "catch" ( "(" IDENTIFIER ")" )? block )? ( "finally" block )? ;
+     */
+    fn try_statement(&mut self) -> Result<Statement, ParseError> {
+        self.expect(Token::LSquirly, "Expected a left brace")?;
+
+        let block = self.block()?;
+
+        let catch = if self.lexer.match_token_and_consume(Token::Catch) {
+            let param = if self.lexer.match_token_and_consume(Token::Lparen) {
+                let param = self.parse_ident()?;
+
+                self.expect(Token::Rparen, "Expected a right parenthesis")?;
+
+                Some(param)
+            } else {
+                None
+            };
+
+            self.expect(Token::LSquirly, "Expected a left brace")?;
+
+            Some(CatchClause {
+                param,
+                body: self.block()?,
+            })
+        } else {
+            None
+        };
+
+        let finally = if self.lexer.match_token_and_consume(Token::Finally) {
+            self.expect(Token::LSquirly, "Expected a left brace")?;
+
+            Some(self.block()?)
+        } else {
+            None
+        };
 
-        return Statement::_while(condition, body);
+        if catch.is_none() && finally.is_none() {
+            return Err(self.error("Expected 'catch' or 'finally' after try block"));
+        }
+
+        return Ok(Statement::_try(block, catch, finally));
+    }
+
+    /**
+     * throw -> "throw" expression ";" ;
+     */
+    fn throw_statement(&mut self) -> Result<Statement, ParseError> {
+        let expression = self.expression()?;
+
+        self.terminator()?;
+
+        return Ok(Statement::_throw(expression));
+    }
+
+    /**
+     * switch -> "switch" "(" expression ")" "{" switchCase* "}" ;
+     * switchCase -> ( "case" expression | "default" ) ":" declaration* ;
+     */
+    fn switch_statement(&mut self) -> Result<Statement, ParseError> {
+        self.expect(Token::Lparen, "Expected a left parenthesis")?;
+
+        let discriminant = self.expression()?;
+
+        self.expect(Token::Rparen, "Expected a right parenthesis")?;
+        self.expect(Token::LSquirly, "Expected a left brace")?;
+
+        self.switch_depth += 1;
+        let cases = self.switch_cases();
+        self.switch_depth -= 1;
+
+        return Ok(Statement::_switch(discriminant, cases?));
+    }
+
+    fn switch_cases(&mut self) -> Result<Vec<SwitchCase>, ParseError> {
+        let mut cases: Vec<SwitchCase> = Vec::new();
+
+        while !self.lexer.match_token_and_consume(Token::RSquirly) {
+            let test = match self.lexer.next_token() {
+                Token::Case => Some(self.expression()?),
+                Token::Default if cases.iter().any(|case| case.test.is_none()) => {
+                    return Err(self.error_at("Cannot have more than one default clause"))
+                }
+                Token::Default => None,
+                token => return Err(self.unexpected(token, "Expected 'case' or 'default'")),
+            };
+
+            cases.push(self.switch_case(test)?);
+        }
+
+        return Ok(cases);
+    }
+
+    fn switch_case(&mut self, test: Option<Expression>) -> Result<SwitchCase, ParseError> {
+        self.expect(Token::Colon, "Expected a colon after case")?;
+
+        let mut body = Vec::new();
+
+        loop {
+            match self.lexer.peek_token() {
+                Token::Case | Token::Default | Token::RSquirly | Token::Eof => break,
+                _ if self.empty_statement() => {}
+                _ => {
+                    body.push(self.declaration()?);
+                    self.lexer.match_token_and_consume(Token::Semicolon);
+                }
+            }
+        }
+
+        return Ok(SwitchCase { test, body });
     }
 
     /**
-     * for -> "for" "(" ( varDecl | expression | ";" ) expression? ";" expression? ")" statement ;
+     * for -> "for" "(" ( varDecl | expression ";" | ";" ) expression? ";" expression? ")" statement ;
      */
-    pub fn for_statement(&mut self) -> Statement {
-        self.expect(Token::Lparen, "Expected a left parenthesis");
+    pub fn for_statement(&mut self) -> Result<Statement, ParseError> {
+        self.expect(Token::Lparen, "Expected a left parenthesis")?;
+
+        // a line break can't stand in for the semicolons inside the parentheses
+        let initializer = if self.lexer.match_token_and_consume(Token::Let) {
+            let declaration = self.var_declarations()?;
+
+            self.expect(Token::Semicolon, "Expected a semicolon")?;
+
+            Some(declaration)
+        } else if self.lexer.match_token_and_consume(Token::Semicolon) {
+            None
+        } else {
+            let expression = self.expression()?;
+
+            self.expect(Token::Semicolon, "Expected a semicolon")?;
 
-        let initializer = match self.lexer.next_token() {
-            Token::Let => Some(self.var_decl()),
-            Token::Semicolon => None,
-            _ => Some(self.expression_statement()),
+            Some(Statement::_expression(expression))
         };
 
         let condition = if self.lexer.peek_token() != Token::Semicolon {
-            self.expression()
+            self.expression()?
         } else {
             Expression::Literal(ParserValue::Bool(true))
         };
 
-        self.expect(Token::Semicolon, "Expected a semicolon");
+        self.expect(Token::Semicolon, "Expected a semicolon")?;
 
         let increment = if self.lexer.peek_token() != Token::Rparen {
-            Some(self.expression())
+            Some(self.expression()?)
         } else {
             None
         };
 
-        self.expect(Token::Rparen, "Expected a right parenthesis");
+        self.expect(Token::Rparen, "Expected a right parenthesis")?;
 
-        let mut body = self.statement();
-
-        if let Some(increment) = increment {
-            body = Statement::_block(vec![body, Statement::_expression(increment)]);
-        }
+        let bindings = match &initializer {
+            Some(Statement::Let(declaration)) => declaration
+                .declarations
+                .iter()
+                .flat_map(|(pattern, _)| pattern.idents().iter().cloned())
+                .collect(),
+            _ => vec![],
+        };
 
-        body = Statement::_while(condition, body);
+        let body = self.loop_body()?;
+        let mut body = Statement::_for(bindings, condition, increment, body);
 
         if let Some(initializer) = initializer {
             body = Statement::_block(vec![initializer, body]);
         }
 
+        return Ok(body);
+    }
+
+    fn loop_body(&mut self) -> Result<Statement, ParseError> {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+
         return body;
     }
 
     /**
-     * print -> "print" expression ";" ;
+     * break -> "break" IDENTIFIER? ";" ;
+     * continue -> "continue" IDENTIFIER? ";" ;
      */
-    fn print_statement(&mut self) -> Statement {
-        let expression = self.expression();
+    fn loop_control_statement(&mut self, token: Token) -> Result<Statement, ParseError> {
+        let label_follows =
+            matches!(self.lexer.peek_token(), Token::Ident(_)) && !self.lexer.newline_before_next();
+
+        if label_follows {
+            let label = self.parse_ident()?;
 
-        self.lexer.match_token_and_consume(Token::Semicolon);
+            // only loops can be continued, but any labeled statement can be broken out of
+            let found = self.labels.iter().any(|(name, is_loop)| {
+                *name == label.value() && (*is_loop || token == Token::Break)
+            });
 
-        return Statement::print(expression);
+            if !found {
+                return Err(self.error_at(format!("Undefined label '{}'", label.value())));
+            }
+
+            self.terminator()?;
+
+            return Ok(match token {
+                Token::Break => Statement::_break_to(label),
+                _ => Statement::_continue_to(label),
+            });
+        }
+
+        // switch statements can be broken out of, but not continued
+        let inside_target = match token {
+            Token::Break => self.loop_depth > 0 || self.switch_depth > 0,
+            _ => self.loop_depth > 0,
+        };
+
+        if !inside_target {
+            return Err(self.error_at(format!("Cannot use {:?} outside of a loop", token)));
+        }
+
+        self.terminator()?;
+
+        return Ok(match token {
+            Token::Break => Statement::_break(),
+            _ => Statement::_continue(),
+        });
     }
 
     /**
      * return -> "return" expression? ";" ;
      */
-    fn return_statement(&mut self) -> Statement {
-        let value;
+    fn return_statement(&mut self) -> Result<Statement, ParseError> {
+        // like in JS, a value on the next line is not returned
+        let value = match self.lexer.peek_token() {
+            _ if self.lexer.newline_before_next() => None,
+            Token::Semicolon | Token::RSquirly | Token::Eof => None,
+            _ => Some(self.expression()?),
+        };
 
-        if self.lexer.peek_token() != Token::Semicolon {
-            value = self.expression();
-        } else {
-            value = Expression::Literal(ParserValue::Null);
-        }
+        self.terminator()?;
 
-        return Statement::_return(value);
+        return Ok(Statement::Return(value));
     }
 
     /**
-     * statement -> expr | if | print | for | while | return | block ;
+     * statement -> expr | if | for | while | doWhile | switch | try | throw | return | break
+     *            | continue | labeled | block | ";" ;
      */
-    fn statement(&mut self) -> Statement {
+    fn statement(&mut self) -> Result<Statement, ParseError> {
+        return self.nested(Self::parse_statement);
+    }
+
+    fn parse_statement(&mut self) -> Result<Statement, ParseError> {
+        // where a single statement is expected, like a loop body, an empty one is an empty block
+        if self.empty_statement() {
+            return Ok(Statement::_block(vec![]));
+        }
+
         if self.lexer.match_token_and_consume(Token::If) {
             return self.if_statement();
         }
@@ -262,288 +667,631 @@ impl Parser {
             return self.while_statement();
         }
 
-        if self.lexer.match_token_and_consume(Token::For) {
-            return self.for_statement();
+        if self.lexer.match_token_and_consume(Token::Try) {
+            return self.try_statement();
+        }
+
+        if self.lexer.match_token_and_consume(Token::Throw) {
+            return self.throw_statement();
+        }
+
+        if self.lexer.match_token_and_consume(Token::Switch) {
+            return self.switch_statement();
+        }
+
+        if self.lexer.match_token_and_consume(Token::Do) {
+            return self.do_while_statement();
         }
 
-        if self.lexer.match_token_and_consume(Token::Print) {
-            return self.print_statement();
+        if self.lexer.match_token_and_consume(Token::For) {
+            return self.for_statement();
         }
 
         if self.lexer.match_token_and_consume(Token::Return) {
             return self.return_statement();
         }
 
+        if let Token::Break | Token::Continue = self.lexer.peek_token() {
+            let token = self.lexer.next_token();
+
+            return self.loop_control_statement(token);
+        }
+
         return self.expression_statement();
     }
 
     /**
-     * primary -> NUMBER | STRING | "true" | "false" | null | "(" expression ")" | IDENTIFIER ;
+     * primary -> NUMBER | STRING | "true" | "false" | null | "(" expression ")" | IDENTIFIER | array | object
+     *          | "function" function | "this" ;
      */
-    fn primary(&mut self) -> Expression {
-        match self.lexer.next_token() {
+    fn primary(&mut self) -> Result<Expression, ParseError> {
+        let expr = match self.lexer.next_token() {
             Token::Ident(ident) => Expression::variable(ident),
             Token::Number(int) => Expression::Literal(ParserValue::number(int)),
             Token::String(string) => Expression::Literal(ParserValue::String(string.to_string())),
             Token::True => Expression::Literal(ParserValue::Bool(true)),
             Token::False => Expression::Literal(ParserValue::Bool(false)),
             Token::Null => Expression::Literal(ParserValue::Null),
+            Token::This => Expression::this(),
             Token::Lparen => {
-                let expr = self.expression();
+                let expr = self.expression()?;
 
-                self.expect(Token::Rparen, "Expected a closing parenthesis");
+                self.expect(Token::Rparen, "Expected a closing parenthesis")?;
 
                 Expression::grouping(expr)
             }
-            token => panic!("Expected a primary expression, got {:?}", token),
-        }
+            Token::LBracket => self.array()?,
+            Token::LSquirly => self.object()?,
+            Token::Function => self.function()?,
+            token => return Err(self.unexpected(token, "Expected a primary expression")),
+        };
+
+        return Ok(expr);
     }
 
     /**
-     * arguments -> expression ( "," expression )* ;
+     * array -> "[" ( element ( "," element )* ","? )? "]" ;
+     * element -> "..."? expression ;
      */
-    fn arguments(&mut self) -> Vec<Expression> {
-        let mut arguments = Vec::new();
+    fn array(&mut self) -> Result<Expression, ParseError> {
+        let mut elements = Vec::new();
 
-        if self.lexer.peek_token() != Token::Rparen {
+        if self.lexer.peek_token() != Token::RBracket {
             loop {
-                if arguments.len() >= 255 {
-                    panic!("Cannot have more than 255 arguments");
+                if self.lexer.match_token_and_consume(Token::Ellipsis) {
+                    elements.push(Expression::spread(self.expression()?));
+                } else {
+                    elements.push(self.expression()?);
                 }
 
-                arguments.push(self.expression());
-
-                if !self.lexer.match_token_and_consume(Token::Comma) {
+                if !self.list_continues(Token::RBracket) {
                     break;
                 }
             }
         }
 
-        return arguments;
-    }
-
-    fn finish_call(&mut self, callee: Expression) -> Expression {
-        let arguments = self.arguments();
+        self.expect(Token::RBracket, "Expected a closing bracket")?;
 
-        self.expect(Token::Rparen, "Expected a closing parenthesis");
-
-        return Expression::call(callee, arguments);
+        return Ok(Expression::array(elements));
     }
 
     /**
-     * call -> primary ( "(" arguments? ")" )* ;
+     * object -> "{" ( property ( "," property )* ","? )? "}" ;
+     * property -> ( IDENTIFIER | STRING ) ":" expression | "..." expression ;
      */
-    fn call(&mut self) -> Expression {
-        let mut expr = self.primary();
+    fn object(&mut self) -> Result<Expression, ParseError> {
+        let mut properties = Vec::new();
 
-        while self.lexer.match_token_and_consume(Token::Lparen) {
-            expr = self.finish_call(expr);
-        }
+        if self.lexer.peek_token() != Token::RSquirly {
+            loop {
+                if self.lexer.match_token_and_consume(Token::Ellipsis) {
+                    properties.push(Property::Spread(self.expression()?));
+                } else {
+                    let key = match self.lexer.next_token() {
+                        Token::Ident(key) | Token::String(key) => key,
+                        token => return Err(self.unexpected(token, "Expected a property name")),
+                    };
 
-        return expr;
-    }
+                    self.expect(Token::Colon, "Expected a colon after property name")?;
 
-    /**
-     * unary -> ( "!" | "-" ) unary | call ;
-     */
-    fn unary(&mut self) -> Expression {
-        match self.lexer.peek_token() {
-            Token::Bang | Token::Minus => {
-                let token = self.lexer.next_token();
-                let operator = self.parse_token_to_operator(token);
-                let right = self.unary();
+                    properties.push(Property::Named(key, self.expression()?));
+                }
 
-                return Expression::unary(operator, right);
+                if !self.list_continues(Token::RSquirly) {
+                    break;
+                }
             }
-            _ => return self.call(),
         }
+
+        self.expect(Token::RSquirly, "Expected a closing brace")?;
+
+        return Ok(Expression::Object(properties));
     }
 
     /**
-     * factor -> unary ( ( "/" | "*" ) unary )* ;
+     * arguments -> expression ( "," expression )* ","? ;
      */
-    fn factor(&mut self) -> Expression {
-        let mut expr = self.unary();
+    fn arguments(&mut self) -> Result<Vec<Expression>, ParseError> {
+        let mut arguments = Vec::new();
 
-        loop {
-            match self.lexer.peek_token() {
-                Token::Asterisk | Token::ForwardSlash => {
-                    let token = self.lexer.next_token();
-                    let operator = self.parse_token_to_operator(token);
-                    let right = self.unary();
+        if self.lexer.peek_token() != Token::Rparen {
+            loop {
+                if arguments.len() >= 255 {
+                    return Err(self.error("Cannot have more than 255 arguments"));
+                }
 
-                    expr = Expression::binary(expr, operator, right);
+                arguments.push(self.expression()?);
+
+                if !self.list_continues(Token::Rparen) {
+                    break;
                 }
-                _ => break,
             }
         }
 
-        return expr;
+        return Ok(arguments);
     }
 
-    /**
-     * term -> factor ( ( "-" | "+" ) factor )* ;
-     */
-    fn term(&mut self) -> Expression {
-        let mut expr = self.factor();
-
-        loop {
-            match self.lexer.peek_token() {
-                Token::Plus | Token::Minus => {
-                    let token = self.lexer.next_token();
-                    let operator = self.parse_token_to_operator(token);
-                    let right = self.factor();
+    fn finish_call(&mut self, callee: Expression) -> Result<Expression, ParseError> {
+        let arguments = self.arguments()?;
 
-                    expr = Expression::binary(expr, operator, right);
-                }
-                _ => break,
-            }
-        }
+        self.expect(Token::Rparen, "Expected a closing parenthesis")?;
 
-        return expr;
+        return Ok(Expression::call(callee, arguments));
     }
 
     /**
-     * comparison -> term ( ( ">" | ">=" | "<" | ">" ) term )* ;
+     * call -> primary ( "(" arguments? ")" | "[" expression "]" | "." IDENTIFIER )* ;
      */
-    fn comparison(&mut self) -> Expression {
-        let mut expr = self.term();
+    fn call(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.primary()?;
 
         loop {
-            match self.lexer.peek_token() {
-                Token::GreaterThan
-                | Token::GreaterThanOrEqual
-                | Token::LessThan
-                | Token::LessThanOrEqual => {
-                    let token = self.lexer.next_token();
-                    let operator = self.parse_token_to_operator(token);
-                    let right = self.term();
-
-                    expr = Expression::binary(expr, operator, right);
-                }
-                _ => break,
+            if self.lexer.match_token_and_consume(Token::Lparen) {
+                expr = self.finish_call(expr)?;
+            } else if self.lexer.match_token_and_consume(Token::LBracket) {
+                let index = self.expression()?;
+
+                self.expect(Token::RBracket, "Expected a closing bracket")?;
+
+                expr = Expression::index(expr, index);
+            } else if self.lexer.match_token_and_consume(Token::Dot) {
+                let name = self.parse_ident()?;
+
+                expr = Expression::get(expr, name);
+            } else {
+                break;
             }
         }
 
-        return expr;
+        return Ok(expr);
     }
 
     /**
-     * equality -> comparison ( ( "!=" | "==" ) comparison )* ;
+     * postfix -> call ( "++" | "--" )? ;
      */
-    fn equality(&mut self) -> Expression {
-        let mut expr = self.comparison();
+    fn postfix(&mut self) -> Result<Expression, ParseError> {
+        let expr = self.call()?;
 
-        loop {
-            match self.lexer.peek_token() {
-                Token::Equal | Token::NotEqual => {
-                    let token = self.lexer.next_token();
-                    let operator = self.parse_token_to_operator(token);
-                    let right = self.comparison();
+        // a `++` or `--` on the next line belongs to the next statement
+        if self.lexer.newline_before_next() {
+            return Ok(expr);
+        }
 
-                    expr = Expression::binary(expr, operator, right);
-                }
-                _ => break,
+        if let Token::PlusPlus | Token::MinusMinus = self.lexer.peek_token() {
+            let token = self.lexer.next_token();
+
+            return self.update(token, false, expr);
+        }
+
+        return Ok(expr);
+    }
+
+    fn update(
+        &mut self,
+        token: Token,
+        prefix: bool,
+        target: Expression,
+    ) -> Result<Expression, ParseError> {
+        let operator = self.parse_token_to_operator(token)?;
+
+        if !matches!(
+            target,
+            Expression::Variable { .. } | Expression::Get { .. } | Expression::Index { .. }
+        ) {
+            return Err(self.error_at(match operator {
+                Operator::Increment => "Invalid increment target",
+                _ => "Invalid decrement target",
+            }));
+        }
+
+        return Ok(Expression::update(operator, prefix, target));
+    }
+
+    /**
+     * unary -> ( "!" | "-" | "+" | "~" | "typeof" ) unary | ( "++" | "--" ) unary | postfix ;
+     */
+    fn unary(&mut self) -> Result<Expression, ParseError> {
+        match self.lexer.peek_token() {
+            Token::Plus => {
+                self.lexer.next_token();
+                let right = self.nested(Self::unary)?;
+
+                return Ok(Expression::unary(Operator::UnaryPlus, right));
+            }
+            Token::Bang | Token::Minus | Token::Tilde | Token::Typeof => {
+                let token = self.lexer.next_token();
+                let operator = self.parse_token_to_operator(token)?;
+                let right = self.nested(Self::unary)?;
+
+                return Ok(Expression::unary(operator, right));
             }
+            Token::PlusPlus | Token::MinusMinus => {
+                let token = self.lexer.next_token();
+                let target = self.nested(Self::unary)?;
+
+                return self.update(token, true, target);
+            }
+            _ => return self.postfix(),
+        }
+    }
+
+    /**
+     * power -> unary ( "**" power )? ;
+     */
+    fn power(&mut self) -> Result<Expression, ParseError> {
+        let expr = self.unary()?;
+
+        if self.lexer.match_token_and_consume(Token::StarStar) {
+            // recursing on the right operand makes ** right-associative
+            let right = self.nested(Self::power)?;
+
+            return Ok(Expression::binary(expr, Operator::Power, right));
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * factor -> power ( ( "/" | "*" | "%" ) power )* ;
+     */
+    fn factor(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.power()?;
+
+        while let Token::Asterisk | Token::ForwardSlash | Token::Percent = self.lexer.peek_token() {
+            let token = self.lexer.next_token();
+            let operator = self.parse_token_to_operator(token)?;
+            let right = self.power()?;
+
+            expr = Expression::binary(expr, operator, right);
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * term -> factor ( ( "-" | "+" ) factor )* ;
+     */
+    fn term(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.factor()?;
+
+        while let Token::Plus | Token::Minus = self.lexer.peek_token() {
+            let token = self.lexer.next_token();
+            let operator = self.parse_token_to_operator(token)?;
+            let right = self.factor()?;
+
+            expr = Expression::binary(expr, operator, right);
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * comparison -> shift ( ( ">" | ">=" | "<" | ">" | "in" ) shift )* ;
+     */
+    fn comparison(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.shift()?;
+
+        while let Token::GreaterThan
+        | Token::GreaterThanOrEqual
+        | Token::LessThan
+        | Token::LessThanOrEqual
+        | Token::In = self.lexer.peek_token()
+        {
+            let token = self.lexer.next_token();
+            let operator = self.parse_token_to_operator(token)?;
+            let right = self.shift()?;
+
+            expr = Expression::binary(expr, operator, right);
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * shift -> term ( ( "<<" | ">>" ) term )* ;
+     */
+    fn shift(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.term()?;
+
+        while let Token::ShiftLeft | Token::ShiftRight = self.lexer.peek_token() {
+            let token = self.lexer.next_token();
+            let operator = self.parse_token_to_operator(token)?;
+            let right = self.term()?;
+
+            expr = Expression::binary(expr, operator, right);
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * bit_and -> equality ( "&" equality )* ;
+     */
+    fn bit_and(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.equality()?;
+
+        while self.lexer.match_token_and_consume(Token::Ampersand) {
+            let right = self.equality()?;
+
+            expr = Expression::binary(expr, Operator::BitAnd, right);
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * bit_xor -> bit_and ( "^" bit_and )* ;
+     */
+    fn bit_xor(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.bit_and()?;
+
+        while self.lexer.match_token_and_consume(Token::Caret) {
+            let right = self.bit_and()?;
+
+            expr = Expression::binary(expr, Operator::BitXor, right);
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * bit_or -> bit_xor ( "|" bit_xor )* ;
+     */
+    fn bit_or(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.bit_xor()?;
+
+        while self.lexer.match_token_and_consume(Token::Pipe) {
+            let right = self.bit_xor()?;
+
+            expr = Expression::binary(expr, Operator::BitOr, right);
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * equality -> comparison ( ( "!=" | "==" | "!==" | "===" ) comparison )* ;
+     */
+    fn equality(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.comparison()?;
+
+        while let Token::Equal | Token::NotEqual | Token::StrictEqual | Token::StrictNotEqual =
+            self.lexer.peek_token()
+        {
+            let token = self.lexer.next_token();
+            let operator = self.parse_token_to_operator(token)?;
+            let right = self.comparison()?;
+
+            expr = Expression::binary(expr, operator, right);
         }
 
-        return expr;
+        return Ok(expr);
     }
 
     /**
-     * assignment -> IDENTIFIER "=" assignment | logic_or ;
+     * assignment -> ( call "." )? IDENTIFIER "=" assignment | ternary ;
      */
-    fn assignment(&mut self) -> Expression {
-        let expr = self.or();
+    fn assignment(&mut self) -> Result<Expression, ParseError> {
+        let expr = self.ternary()?;
 
         if self.lexer.match_token_and_consume(Token::Assign) {
-            let ident = match expr {
-                Expression::Variable(ident) => ident,
-                _ => panic!("Expected an identifier"),
+            let (line, column) = self.lexer.position();
+            let value = self.nested(Self::assignment)?;
+
+            return match expr {
+                Expression::Variable { ident, .. } => Ok(Expression::assignement(ident, value)),
+                Expression::Get { object, name } => Ok(Expression::set(*object, name, value)),
+                _ => Err(ParseError::new("Invalid assignment target", line, column)),
             };
+        }
+
+        return Ok(expr);
+    }
+
+    /**
+     * ternary -> nullish ( "?" assignment ":" assignment )? ;
+     */
+    fn ternary(&mut self) -> Result<Expression, ParseError> {
+        let condition = self.nullish()?;
+
+        if self.lexer.match_token_and_consume(Token::Question) {
+            let then_branch = self.nested(Self::assignment)?;
+
+            self.expect(Token::Colon, "Expected a colon in ternary expression")?;
+
+            let else_branch = self.nested(Self::assignment)?;
+
+            return Ok(Expression::ternary(condition, then_branch, else_branch));
+        }
+
+        return Ok(condition);
+    }
+
+    /**
+     * nullish -> logic_or ( "??" logic_or )* ;
+     */
+    fn nullish(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.or()?;
 
-            let value = self.assignment();
+        while self.lexer.match_token_and_consume(Token::QuestionQuestion) {
+            let right = self.or()?;
 
-            return Expression::assignement(ident, value);
+            expr = Expression::logical(expr, Operator::Nullish, right);
         }
 
-        return expr;
+        return Ok(expr);
     }
 
     /**
      * logic_or -> logic_and ( "or" logic_and )* ;
      */
-    fn or(&mut self) -> Expression {
-        let mut expr = self.and();
+    fn or(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.and()?;
 
         while self.lexer.match_token_and_consume(Token::Or) {
             let operator = Operator::Or;
-            let right = self.and();
+            let right = self.and()?;
 
-            expr = Expression::binary(expr, operator, right);
+            expr = Expression::logical(expr, operator, right);
         }
 
-        return expr;
+        return Ok(expr);
     }
 
     /**
-     * logic_and -> equality ( "and" equality )* ;
+     * logic_and -> bit_or ( "and" bit_or )* ;
      */
-    fn and(&mut self) -> Expression {
-        let mut expr = self.equality();
+    fn and(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.bit_or()?;
 
         while self.lexer.match_token_and_consume(Token::And) {
             let operator = Operator::And;
-            let right = self.equality();
+            let right = self.bit_or()?;
 
-            expr = Expression::binary(expr, operator, right); // should we create Expression::logical?
+            expr = Expression::logical(expr, operator, right);
         }
 
-        return expr;
+        return Ok(expr);
     }
 
     /**
      * expression -> assignment ;
      */
-    fn expression(&mut self) -> Expression {
-        if self.lexer.match_token_and_consume(Token::Function) {
-            return self.function();
+    fn expression(&mut self) -> Result<Expression, ParseError> {
+        return self.nested(Self::assignment);
+    }
+
+    /**
+     * Runs `parse` one nesting level deeper, failing instead once the input nests deeper than
+     * `MAX_NESTING_DEPTH`
+     */
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(self.error_at(format!(
+                "Nesting is deeper than the limit of {}",
+                MAX_NESTING_DEPTH
+            )));
         }
 
-        return self.assignment();
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+
+        return result;
     }
 
-    fn parse_ident(&mut self) -> Ident {
+    fn parse_ident(&mut self) -> Result<Ident, ParseError> {
         match self.lexer.next_token() {
-            Token::Ident(ident) => return Ident::new(ident),
-            _ => panic!("Expected an identifier"),
+            Token::Ident(ident) => return Ok(Ident::new(ident)),
+            token => return Err(self.unexpected(token, "Expected an identifier")),
         }
     }
 
-    fn parse_token_to_operator(&mut self, token: Token) -> Operator {
-        match token {
+    fn parse_token_to_operator(&mut self, token: Token) -> Result<Operator, ParseError> {
+        let operator = match token {
             Token::Plus => Operator::Plus,
             Token::Minus => Operator::Minus,
             Token::Asterisk => Operator::Asterisk,
             Token::ForwardSlash => Operator::Slash,
+            Token::Percent => Operator::Modulo,
+            Token::StarStar => Operator::Power,
+            Token::PlusPlus => Operator::Increment,
+            Token::MinusMinus => Operator::Decrement,
             Token::Bang => Operator::Bang,
+            Token::Typeof => Operator::Typeof,
             Token::Equal => Operator::Equal,
             Token::NotEqual => Operator::NotEqual,
+            Token::StrictEqual => Operator::StrictEqual,
+            Token::StrictNotEqual => Operator::StrictNotEqual,
             Token::And => Operator::And,
             Token::Or => Operator::Or,
+            Token::QuestionQuestion => Operator::Nullish,
             Token::LessThan => Operator::LessThan,
             Token::LessThanOrEqual => Operator::LessThanOrEqual,
             Token::GreaterThan => Operator::GreaterThan,
             Token::GreaterThanOrEqual => Operator::GreaterThanOrEqual,
-            token => panic!("Expected an operator, got {:?}", token),
+            Token::In => Operator::In,
+            Token::Ampersand => Operator::BitAnd,
+            Token::Pipe => Operator::BitOr,
+            Token::Caret => Operator::BitXor,
+            Token::Tilde => Operator::BitNot,
+            Token::ShiftLeft => Operator::ShiftLeft,
+            Token::ShiftRight => Operator::ShiftRight,
+            token => return Err(self.unexpected(token, "Expected an operator")),
+        };
+
+        return Ok(operator);
+    }
+
+    /**
+     * Statements end with a semicolon, which may only be left out before a "}", the end of input or
+     * a line break
+     */
+    fn terminator(&mut self) -> Result<(), ParseError> {
+        if self.lexer.match_token_and_consume(Token::Semicolon) || self.lexer.newline_before_next()
+        {
+            return Ok(());
+        }
+
+        match self.lexer.peek_token() {
+            Token::RSquirly | Token::Eof => return Ok(()),
+            _ => return Err(self.error("Expected a semicolon")),
         }
     }
 
-    fn expect(&mut self, token: Token, message: &str) {
+    /**
+     * Consumes the comma after an item of a list that ends with `closing`, returning whether
+     * another item follows. A comma right before `closing` is a trailing comma and ends the list
+     */
+    fn list_continues(&mut self, closing: Token) -> bool {
+        return self.lexer.match_token_and_consume(Token::Comma)
+            && self.lexer.peek_token() != closing;
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<(), ParseError> {
         if !self.lexer.match_token_and_consume(token) {
-            panic!("{}, got {:?}", message, self.lexer.next_token());
+            return Err(self.error(message));
+        }
+
+        return Ok(());
+    }
+
+    /**
+     * Builds an error about the next token, consuming it
+     */
+    fn error(&mut self, message: &str) -> ParseError {
+        let token = self.lexer.next_token();
+
+        return self.unexpected(token, message);
+    }
+
+    /**
+     * Builds an error about `token`, which must be the token the lexer just returned
+     */
+    fn unexpected(&self, token: Token, message: &str) -> ParseError {
+        match token {
+            Token::Error(error) => return self.error_at(error),
+            Token::Eof => {
+                let (line, column) = self.lexer.position();
+
+                return ParseError::unexpected_eof(
+                    format!("{}, got {:?}", message, token),
+                    line,
+                    column,
+                );
+            }
+            token => return self.error_at(format!("{}, got {:?}", message, token)),
         }
     }
+
+    fn error_at<S: Into<String>>(&self, message: S) -> ParseError {
+        let (line, column) = self.lexer.position();
+
+        return ParseError::new(message, line, column);
+    }
 }
 
 #[cfg(test)]
@@ -554,10 +1302,118 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn parse_to_json() {
+        assert_eq!(
+            Parser::parse_to_json("let x = 1 + 2;").unwrap(),
+            concat!(
+                r#"[{"Let":{"declarations":[[{"Ident":"x"},{"Binary":{"#,
+                r#""left":{"Literal":{"Number":"1"}},"operator":"Plus","#,
+                r#""right":{"Literal":{"Number":"2"}}}}]],"constant":false}}]"#
+            )
+        );
+    }
+
+    #[test]
+    fn parse_expression() {
+        let expression = Parser::new("1 + 2 * 3").parse_expression().unwrap();
+
+        assert_eq!(
+            expression,
+            Expression::binary(
+                Expression::literal(ParserValue::number("1")),
+                Operator::Plus,
+                Expression::binary(
+                    Expression::literal(ParserValue::number("2")),
+                    Operator::Asterisk,
+                    Expression::literal(ParserValue::number("3")),
+                ),
+            )
+        );
+
+        assert!(Parser::new("1 +").parse_expression().is_err());
+        assert_eq!(
+            Parser::new("1 2").parse_expression().unwrap_err().message,
+            "Expected the end of the expression, got Number(\"2\")"
+        );
+    }
+
+    #[test]
+    fn let_statement_with_multiple_declarators() {
+        let mut parser = Parser::new(s!("let a = 1, b = 2, c;"));
+        let stmt = parser.parse().unwrap();
+
+        assert_eq!(
+            stmt,
+            vec![Statement::_let_declarations(vec![
+                (
+                    Ident::new("a"),
+                    Some(Expression::literal(ParserValue::number("1")))
+                ),
+                (
+                    Ident::new("b"),
+                    Some(Expression::literal(ParserValue::number("2")))
+                ),
+                (Ident::new("c"), None),
+            ])]
+        );
+    }
+
+    #[test]
+    fn destructuring_declarations() {
+        let mut parser = Parser::new(s!("let [a, b] = arr, {} = o; const { x, y } = o;"));
+        let stmt = parser.parse().unwrap();
+
+        assert_eq!(
+            stmt,
+            vec![
+                Statement::_let_declarations(vec![
+                    (
+                        Pattern::Array(vec![Ident::new("a"), Ident::new("b")]),
+                        Some(Expression::variable("arr"))
+                    ),
+                    (Pattern::Object(vec![]), Some(Expression::variable("o"))),
+                ]),
+                Statement::_const(
+                    Pattern::Object(vec![Ident::new("x"), Ident::new("y")]),
+                    Expression::variable("o")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn destructuring_without_initializer() {
+        let mut parser = Parser::new(s!("let [a, b];"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Expected an initializer for destructuring declaration, got Semicolon"
+        );
+
+        let mut parser = Parser::new(s!("let {a: b} = o;"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Expected a closing brace, got Colon"
+        );
+    }
+
+    #[test]
+    fn const_without_initializer_among_declarators() {
+        let mut parser = Parser::new(s!("const a = 1, b;"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Expected an initializer for const declaration, got Semicolon"
+        );
+    }
+
     #[test]
     fn let_statement() {
         let mut parser = Parser::new(s!("let a = 1;"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         assert_eq!(
             stmt,
@@ -571,7 +1427,7 @@ mod tests {
     #[test]
     fn literal_expression() {
         let mut parser = Parser::new(s!("1;"));
-        let expr = parser.expression();
+        let expr = parser.expression().unwrap();
 
         assert_eq!(expr, Expression::literal(ParserValue::number("1")));
     }
@@ -579,7 +1435,7 @@ mod tests {
     #[test]
     fn binary_expression() {
         let mut parser = Parser::new(s!("1 + 2;"));
-        let expr = parser.expression();
+        let expr = parser.expression().unwrap();
 
         assert_eq!(
             expr,
@@ -594,7 +1450,7 @@ mod tests {
     #[test]
     fn grouping_expression() {
         let mut parser = Parser::new(s!("(1 + 2);"));
-        let expr = parser.expression();
+        let expr = parser.expression().unwrap();
 
         assert_eq!(
             expr,
@@ -607,74 +1463,231 @@ mod tests {
     }
 
     #[test]
-    fn unary_expression() {
-        let mut parser = Parser::new(s!("!true;"));
-        let expr = parser.expression();
+    fn bitwise_precedence() {
+        let mut parser = Parser::new(s!("a | b & c == d << 1 + 2;"));
+        let expr = parser.expression().unwrap();
 
         assert_eq!(
             expr,
-            Expression::unary(Operator::Bang, Expression::literal(ParserValue::Bool(true)))
+            Expression::binary(
+                Expression::variable("a"),
+                Operator::BitOr,
+                Expression::binary(
+                    Expression::variable("b"),
+                    Operator::BitAnd,
+                    Expression::binary(
+                        Expression::variable("c"),
+                        Operator::Equal,
+                        Expression::binary(
+                            Expression::variable("d"),
+                            Operator::ShiftLeft,
+                            Expression::binary(
+                                Expression::literal(ParserValue::number("1")),
+                                Operator::Plus,
+                                Expression::literal(ParserValue::number("2")),
+                            ),
+                        ),
+                    ),
+                ),
+            )
         );
     }
 
     #[test]
-    fn unary_expression_with_grouping() {
-        let mut parser = Parser::new(s!("!(!true);"));
-        let expr = parser.expression();
+    fn update_expressions() {
+        let mut parser = Parser::new(s!("i++ - --o.a;"));
+        let expr = parser.expression().unwrap();
 
         assert_eq!(
             expr,
-            Expression::unary(
-                Operator::Bang,
-                Expression::grouping(Expression::unary(
-                    Operator::Bang,
-                    Expression::literal(ParserValue::Bool(true))
-                ))
+            Expression::binary(
+                Expression::update(Operator::Increment, false, Expression::variable("i")),
+                Operator::Minus,
+                Expression::update(
+                    Operator::Decrement,
+                    true,
+                    Expression::get(Expression::variable("o"), Ident::new("a")),
+                ),
             )
         );
-    }
 
-    #[test]
-    fn unary_expression_with_grouping_and_binary() {
-        let mut parser = Parser::new(s!("!(!true + 1);"));
-        let expr = parser.expression();
+        let mut parser = Parser::new(s!("a[0]++;"));
+        let expr = parser.expression().unwrap();
 
         assert_eq!(
             expr,
-            Expression::unary(
-                Operator::Bang,
-                Expression::grouping(Expression::binary(
-                    Expression::unary(Operator::Bang, Expression::literal(ParserValue::Bool(true))),
-                    Operator::Plus,
-                    Expression::literal(ParserValue::number("1")),
-                ))
+            Expression::update(
+                Operator::Increment,
+                false,
+                Expression::index(
+                    Expression::variable("a"),
+                    Expression::literal(ParserValue::number("0")),
+                ),
             )
         );
     }
 
     #[test]
-    fn binary_expression_with_precedence() {
-        let mut parser = Parser::new(s!("1 + 2 * 3;"));
-        let expr = parser.expression();
+    fn invalid_update_target() {
+        let mut parser = Parser::new(s!("1++;"));
 
         assert_eq!(
-            expr,
-            Expression::binary(
-                Expression::literal(ParserValue::number("1")),
-                Operator::Plus,
-                Expression::binary(
-                    Expression::literal(ParserValue::number("2")),
-                    Operator::Asterisk,
-                    Expression::literal(ParserValue::number("3")),
-                ),
-            )
+            parser.parse().unwrap_err().message,
+            "Invalid increment target"
         );
-    }
+
+        let mut parser = Parser::new(s!("--f();"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Invalid decrement target"
+        );
+    }
+
+    #[test]
+    fn power_expression() {
+        let mut parser = Parser::new(s!("2 * 2 ** 3 ** 2;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::binary(
+                Expression::literal(ParserValue::number("2")),
+                Operator::Asterisk,
+                Expression::binary(
+                    Expression::literal(ParserValue::number("2")),
+                    Operator::Power,
+                    Expression::binary(
+                        Expression::literal(ParserValue::number("3")),
+                        Operator::Power,
+                        Expression::literal(ParserValue::number("2")),
+                    ),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn strict_equality_expression() {
+        let mut parser = Parser::new(s!("a === b !== c;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::binary(
+                Expression::binary(
+                    Expression::variable("a"),
+                    Operator::StrictEqual,
+                    Expression::variable("b"),
+                ),
+                Operator::StrictNotEqual,
+                Expression::variable("c"),
+            )
+        );
+    }
+
+    #[test]
+    fn unary_expression() {
+        let mut parser = Parser::new(s!("!true;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::unary(Operator::Bang, Expression::literal(ParserValue::Bool(true)))
+        );
+    }
+
+    #[test]
+    fn unary_plus_expression() {
+        let mut parser = Parser::new(s!("1 + +\"2\";"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::binary(
+                Expression::literal(ParserValue::number("1")),
+                Operator::Plus,
+                Expression::unary(
+                    Operator::UnaryPlus,
+                    Expression::literal(ParserValue::string("2"))
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn unary_expression_with_grouping() {
+        let mut parser = Parser::new(s!("!(!true);"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::unary(
+                Operator::Bang,
+                Expression::grouping(Expression::unary(
+                    Operator::Bang,
+                    Expression::literal(ParserValue::Bool(true))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn unary_expression_with_grouping_and_binary() {
+        let mut parser = Parser::new(s!("!(!true + 1);"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::unary(
+                Operator::Bang,
+                Expression::grouping(Expression::binary(
+                    Expression::unary(Operator::Bang, Expression::literal(ParserValue::Bool(true))),
+                    Operator::Plus,
+                    Expression::literal(ParserValue::number("1")),
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn typeof_expression() {
+        let mut parser = Parser::new(s!("typeof a == \"number\";"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::binary(
+                Expression::unary(Operator::Typeof, Expression::variable("a")),
+                Operator::Equal,
+                Expression::literal(ParserValue::String("number".to_string())),
+            )
+        );
+    }
+
+    #[test]
+    fn binary_expression_with_precedence() {
+        let mut parser = Parser::new(s!("1 + 2 * 3;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::binary(
+                Expression::literal(ParserValue::number("1")),
+                Operator::Plus,
+                Expression::binary(
+                    Expression::literal(ParserValue::number("2")),
+                    Operator::Asterisk,
+                    Expression::literal(ParserValue::number("3")),
+                ),
+            )
+        );
+    }
 
     #[test]
     fn binary_expression_with_precedence_and_grouping() {
         let mut parser = Parser::new(s!("(1 + 2) * 3;"));
-        let expr = parser.expression();
+        let expr = parser.expression().unwrap();
 
         assert_eq!(
             expr,
@@ -690,13 +1703,291 @@ mod tests {
         );
     }
 
+    #[test]
+    fn modulo_expression_with_precedence() {
+        let mut parser = Parser::new(s!("1 + 7 % 3;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::binary(
+                Expression::literal(ParserValue::number("1")),
+                Operator::Plus,
+                Expression::binary(
+                    Expression::literal(ParserValue::number("7")),
+                    Operator::Modulo,
+                    Expression::literal(ParserValue::number("3")),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn ternary_expression() {
+        let mut parser = Parser::new(s!("a < 1 ? b : c ? 2 : 3;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::ternary(
+                Expression::binary(
+                    Expression::variable("a"),
+                    Operator::LessThan,
+                    Expression::literal(ParserValue::number("1")),
+                ),
+                Expression::variable("b"),
+                Expression::ternary(
+                    Expression::variable("c"),
+                    Expression::literal(ParserValue::number("2")),
+                    Expression::literal(ParserValue::number("3")),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn logical_expression_with_precedence() {
+        let mut parser = Parser::new(s!("a || b && c == 1;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::logical(
+                Expression::variable("a"),
+                Operator::Or,
+                Expression::logical(
+                    Expression::variable("b"),
+                    Operator::And,
+                    Expression::binary(
+                        Expression::variable("c"),
+                        Operator::Equal,
+                        Expression::literal(ParserValue::number("1")),
+                    ),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn nullish_expression() {
+        let mut parser = Parser::new(s!("a ?? b || c ? 1 : 2;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::ternary(
+                Expression::logical(
+                    Expression::variable("a"),
+                    Operator::Nullish,
+                    Expression::logical(
+                        Expression::variable("b"),
+                        Operator::Or,
+                        Expression::variable("c"),
+                    ),
+                ),
+                Expression::literal(ParserValue::number("1")),
+                Expression::literal(ParserValue::number("2")),
+            )
+        );
+    }
+
+    #[test]
+    fn array_expression() {
+        let mut parser = Parser::new(s!("[1, [], [a + 1]];"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::array(vec![
+                Expression::literal(ParserValue::number("1")),
+                Expression::array(vec![]),
+                Expression::array(vec![Expression::binary(
+                    Expression::variable("a"),
+                    Operator::Plus,
+                    Expression::literal(ParserValue::number("1")),
+                )]),
+            ])
+        );
+    }
+
+    #[test]
+    fn index_expression() {
+        let mut parser = Parser::new(s!("a[0][i + 1](2);"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::call(
+                Expression::index(
+                    Expression::index(
+                        Expression::variable("a"),
+                        Expression::literal(ParserValue::number("0")),
+                    ),
+                    Expression::binary(
+                        Expression::variable("i"),
+                        Operator::Plus,
+                        Expression::literal(ParserValue::number("1")),
+                    ),
+                ),
+                vec![Expression::literal(ParserValue::number("2"))],
+            )
+        );
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let cases = [
+            ("f(1, 2,);", "f(1, 2);"),
+            ("function f(a, b,) {}", "function f(a, b) {}"),
+            ("let f = function(a,) {};", "let f = function(a) {};"),
+            ("[1, [2,],];", "[1, [2]];"),
+            ("let o = { a: 1, b: {},};", "let o = { a: 1, b: {} };"),
+            ("let [a, b,] = c, { d, } = e;", "let [a, b] = c, { d } = e;"),
+        ];
+
+        for (trailing, expected) in cases {
+            assert_eq!(
+                Parser::new(trailing).parse(),
+                Parser::new(expected).parse(),
+                "{}",
+                trailing
+            );
+        }
+
+        assert!(Parser::new("f(,);").parse().is_err());
+        assert!(Parser::new("[1,,];").parse().is_err());
+        assert!(Parser::new("f(1,,);").parse().is_err());
+    }
+
+    #[test]
+    fn object_expression() {
+        let mut parser = Parser::new(s!("{ name: \"x\", \"age\": 3, inner: {} };"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::object(vec![
+                (s!("name"), Expression::literal(ParserValue::string("x"))),
+                (s!("age"), Expression::literal(ParserValue::number("3"))),
+                (s!("inner"), Expression::object(vec![])),
+            ])
+        );
+    }
+
+    #[test]
+    fn spread_expression() {
+        let mut parser = Parser::new(s!("[...a, 1];"));
+
+        assert_eq!(
+            parser.expression().unwrap(),
+            Expression::array(vec![
+                Expression::spread(Expression::variable("a")),
+                Expression::literal(ParserValue::number("1")),
+            ])
+        );
+
+        let mut parser = Parser::new(s!("{ ...a, b: 2 };"));
+
+        assert_eq!(
+            parser.expression().unwrap(),
+            Expression::Object(vec![
+                Property::Spread(Expression::variable("a")),
+                Property::Named(s!("b"), Expression::literal(ParserValue::number("2"))),
+            ])
+        );
+    }
+
+    #[test]
+    fn get_expression() {
+        let mut parser = Parser::new(s!("a.b[0].c;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::get(
+                Expression::index(
+                    Expression::get(Expression::variable("a"), Ident::new("b")),
+                    Expression::literal(ParserValue::number("0")),
+                ),
+                Ident::new("c"),
+            )
+        );
+    }
+
+    #[test]
+    fn member_call_chains() {
+        let mut parser = Parser::new(s!("a.b(1);"));
+
+        assert_eq!(
+            parser.expression().unwrap(),
+            Expression::call(
+                Expression::get(Expression::variable("a"), Ident::new("b")),
+                vec![Expression::literal(ParserValue::number("1"))],
+            )
+        );
+
+        let mut parser = Parser::new(s!("a.b.c;"));
+
+        assert_eq!(
+            parser.expression().unwrap(),
+            Expression::get(
+                Expression::get(Expression::variable("a"), Ident::new("b")),
+                Ident::new("c"),
+            )
+        );
+
+        let mut parser = Parser::new(s!("a.b.c(1)(2);"));
+
+        assert_eq!(
+            parser.expression().unwrap(),
+            Expression::call(
+                Expression::call(
+                    Expression::get(
+                        Expression::get(Expression::variable("a"), Ident::new("b")),
+                        Ident::new("c"),
+                    ),
+                    vec![Expression::literal(ParserValue::number("1"))],
+                ),
+                vec![Expression::literal(ParserValue::number("2"))],
+            )
+        );
+    }
+
+    #[test]
+    fn this_expression() {
+        let mut parser = Parser::new(s!("this.name;"));
+
+        assert_eq!(
+            parser.expression().unwrap(),
+            Expression::get(Expression::this(), Ident::new("name"))
+        );
+    }
+
+    #[test]
+    fn set_expression() {
+        let mut parser = Parser::new(s!("a.b.c = d = 1;"));
+        let expr = parser.expression().unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::set(
+                Expression::get(Expression::variable("a"), Ident::new("b")),
+                Ident::new("c"),
+                Expression::assignement(
+                    Ident::new("d"),
+                    Expression::literal(ParserValue::number("1"))
+                ),
+            )
+        );
+    }
+
     /**
      * STATEMENTS
      */
     #[test]
     fn let_statement_uninitialized() {
         let mut parser = Parser::new(s!("let a;"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         for stmt in stmt {
             assert_eq!(stmt, Statement::_let(Ident::new("a"), None,));
@@ -706,7 +1997,7 @@ mod tests {
     #[test]
     fn let_statement_initialized() {
         let mut parser = Parser::new(s!("let a = 1;"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         for stmt in stmt {
             assert_eq!(
@@ -719,10 +2010,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn const_statement() {
+        let mut parser = Parser::new(s!("const a = 1;"));
+        let stmt = parser.parse().unwrap();
+
+        assert_eq!(
+            stmt,
+            vec![Statement::_const(
+                Ident::new("a"),
+                Expression::literal(ParserValue::number("1"))
+            )]
+        );
+    }
+
+    #[test]
+    fn const_statement_uninitialized() {
+        let mut parser = Parser::new(s!("const a;"));
+
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::new(
+                "Expected an initializer for const declaration, got Semicolon",
+                1,
+                8
+            ))
+        );
+    }
+
     // #[test]
     // fn return_statement() {
     //     let mut parser = Parser::new(s!("return 1;"));
-    //     let stmt = parser.statement();
+    //     let stmt = parser.statement().unwrap();
 
     //     assert_eq!(
     //         stmt,
@@ -735,7 +2054,7 @@ mod tests {
     #[test]
     fn expression_statement() {
         let mut parser = Parser::new(s!("1;"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         for stmt in stmt {
             assert_eq!(
@@ -748,7 +2067,7 @@ mod tests {
     #[test]
     fn block_statement() {
         let mut parser = Parser::new(s!("{ 1; }"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         for stmt in stmt {
             assert_eq!(
@@ -760,10 +2079,132 @@ mod tests {
         }
     }
 
+    #[test]
+    fn statements_across_lines() {
+        let mut parser = Parser::new(s!("let a = 1;\nlet b =\n  a\n  + 2;\r\nb;"));
+
+        assert_eq!(
+            parser.parse().unwrap(),
+            vec![
+                Statement::_let(
+                    Ident::new("a"),
+                    Some(Expression::literal(ParserValue::number("1"))),
+                ),
+                Statement::_let(
+                    Ident::new("b"),
+                    Some(Expression::binary(
+                        Expression::variable("a"),
+                        Operator::Plus,
+                        Expression::literal(ParserValue::number("2")),
+                    )),
+                ),
+                Statement::_expression(Expression::variable("b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn newlines_end_statements() {
+        let mut parser = Parser::new(s!("let a = 1\nlet b = 2\na = b\n"));
+
+        assert_eq!(
+            parser.parse().unwrap(),
+            vec![
+                Statement::_let(
+                    Ident::new("a"),
+                    Some(Expression::literal(ParserValue::number("1"))),
+                ),
+                Statement::_let(
+                    Ident::new("b"),
+                    Some(Expression::literal(ParserValue::number("2"))),
+                ),
+                Statement::_expression(Expression::assignement(
+                    Ident::new("a"),
+                    Expression::variable("b"),
+                )),
+            ]
+        );
+
+        let mut parser = Parser::new(s!("a\n++b\nreturn\na"));
+
+        assert_eq!(
+            parser.parse().unwrap(),
+            vec![
+                Statement::_expression(Expression::variable("a")),
+                Statement::_expression(Expression::update(
+                    Operator::Increment,
+                    true,
+                    Expression::variable("b"),
+                )),
+                Statement::Return(None),
+                Statement::_expression(Expression::variable("a")),
+            ]
+        );
+
+        // an expression that can't end yet carries on across the line break
+        let mut parser = Parser::new(s!("let c = a\n  + 1\n"));
+
+        assert_eq!(
+            parser.parse().unwrap(),
+            vec![Statement::_let(
+                Ident::new("c"),
+                Some(Expression::binary(
+                    Expression::variable("a"),
+                    Operator::Plus,
+                    Expression::literal(ParserValue::number("1")),
+                )),
+            )]
+        );
+
+        // a semicolon is still needed between statements on the same line
+        assert_eq!(
+            Parser::new(s!("let a = 1 let b = 2"))
+                .parse()
+                .unwrap_err()
+                .message,
+            "Expected a semicolon, got Let"
+        );
+    }
+
+    #[test]
+    fn empty_statements() {
+        assert_eq!(Parser::new(";").parse().unwrap(), vec![]);
+        assert_eq!(Parser::new(";;").parse().unwrap(), vec![]);
+        assert_eq!(
+            Parser::new("let a = 1;;").parse().unwrap(),
+            vec![Statement::_let(
+                Ident::new("a"),
+                Some(Expression::literal(ParserValue::number("1"))),
+            )]
+        );
+        assert_eq!(
+            Parser::new("{ ; } switch (a) { case 1: ; }")
+                .parse()
+                .unwrap(),
+            vec![
+                Statement::_block(vec![]),
+                Statement::_switch(
+                    Expression::variable("a"),
+                    vec![SwitchCase {
+                        test: Some(Expression::literal(ParserValue::number("1"))),
+                        body: vec![],
+                    }],
+                ),
+            ]
+        );
+        assert_eq!(
+            Parser::new("while (a);").parse().unwrap(),
+            vec![Statement::_while(
+                Expression::variable("a"),
+                Statement::_block(vec![])
+            )]
+        );
+    }
+
     #[test]
     fn empty_block_statement() {
         let mut parser = Parser::new(s!("{ }"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         for stmt in stmt {
             assert_eq!(stmt, Statement::_block(vec![]));
@@ -773,7 +2214,7 @@ mod tests {
     #[test]
     fn if_statement() {
         let mut parser = Parser::new(s!("if (true) { 1; }"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         for stmt in stmt {
             assert_eq!(
@@ -789,10 +2230,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn else_if_chain() {
+        let mut parser = Parser::new(s!("if (a) 1; else if (b) 2; else 3;"));
+        let stmt = parser.parse().unwrap();
+
+        let number = |n: &str| Statement::_expression(Expression::literal(ParserValue::number(n)));
+
+        assert_eq!(
+            stmt,
+            vec![Statement::_if(
+                Expression::variable("a"),
+                number("1"),
+                Some(Statement::_if(
+                    Expression::variable("b"),
+                    number("2"),
+                    Some(number("3")),
+                )),
+            )]
+        );
+    }
+
+    #[test]
+    fn dangling_else_binds_to_nearest_if() {
+        let mut parser = Parser::new(s!("if (a) if (b) 1; else 2;"));
+        let stmt = parser.parse().unwrap();
+
+        let number = |n: &str| Statement::_expression(Expression::literal(ParserValue::number(n)));
+
+        assert_eq!(
+            stmt,
+            vec![Statement::_if(
+                Expression::variable("a"),
+                Statement::_if(Expression::variable("b"), number("1"), Some(number("2"))),
+                None,
+            )]
+        );
+    }
+
     #[test]
     fn function_statement() {
         let mut parser = Parser::new(s!("function a() { let b = 1; }"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         for stmt in stmt {
             assert_eq!(
@@ -812,7 +2291,7 @@ mod tests {
     // #[test]
     // fn if() {
     //     let mut parser = Parser::new(s!("if (a) { } else { a = true; }"));
-    //     let stmt = parser.parse();
+    //     let stmt = parser.parse().unwrap();
 
     //     for stmt in stmt {
     //         assert_eq!(
@@ -831,20 +2310,420 @@ mod tests {
     //     }
     // }
 
+    #[test]
+    fn function_expressions() {
+        let mut parser = Parser::new(s!("f = function() {}; g(function named(a) {});"));
+        let stmt = parser.parse().unwrap();
+
+        assert_eq!(
+            stmt,
+            vec![
+                Statement::_expression(Expression::assignement(
+                    Ident::new("f"),
+                    Expression::literal(ParserValue::function(
+                        None,
+                        vec![],
+                        BlockStatement::new(vec![])
+                    )),
+                )),
+                Statement::_expression(Expression::call(
+                    Expression::variable("g"),
+                    vec![Expression::literal(ParserValue::function(
+                        Some(Ident::new("named")),
+                        vec![Ident::new("a")],
+                        BlockStatement::new(vec![])
+                    ))],
+                )),
+            ]
+        );
+    }
+
     #[test]
     fn return_statement() {
         let mut parser = Parser::new(s!("return 1; return; return a;"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         let expected = vec![
             Statement::_return(Expression::literal(ParserValue::number("1"))),
-            Statement::_return(Expression::literal(ParserValue::Null)),
+            Statement::Return(None),
             Statement::_return(Expression::variable("a")),
         ];
 
         assert_eq!(stmt, expected);
     }
 
+    #[test]
+    fn expect_reports_position() {
+        let mut parser = Parser::new(s!("let a = 1;\nwhile (a < 2 { a = a + 1; }"));
+
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::new(
+                "Expected a right parenthesis, got LSquirly",
+                2,
+                14
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_semicolon() {
+        let mut parser = Parser::new(s!("let a = 1 let b = 2;"));
+
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::new("Expected a semicolon, got Let", 1, 11))
+        );
+    }
+
+    #[test]
+    fn unclosed_parenthesis() {
+        let mut parser = Parser::new(s!("(1 + 2"));
+
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::unexpected_eof(
+                "Expected a closing parenthesis, got Eof",
+                1,
+                7
+            ))
+        );
+    }
+
+    #[test]
+    fn unexpected_token() {
+        let mut parser = Parser::new(s!("let a = * 2;"));
+        let error = parser.parse().unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError::new("Expected a primary expression, got Asterisk", 1, 9)
+        );
+        assert_eq!(
+            error.to_string(),
+            "Expected a primary expression, got Asterisk at line 1 column 9"
+        );
+    }
+
+    #[test]
+    fn unexpected_eof() {
+        for code in [
+            "let a = 1 +",
+            "f(1, 2",
+            "if (a) { let b = 1;",
+            "let o = { a: ",
+        ] {
+            let error = Parser::new(code).parse().unwrap_err();
+
+            assert!(error.is_unexpected_eof(), "{}: {:?}", code, error);
+        }
+
+        assert_eq!(
+            Parser::new("let a = 1 +").parse().unwrap_err(),
+            ParseError::unexpected_eof("Expected a primary expression, got Eof", 1, 12)
+        );
+        assert!(!Parser::new("let a = 1 + ;")
+            .parse()
+            .unwrap_err()
+            .is_unexpected_eof());
+    }
+
+    #[test]
+    fn complete_input() {
+        assert!(!Parser::is_complete("function f() {"));
+        assert!(!Parser::is_complete("let a = (1 +"));
+        assert!(Parser::is_complete("function f() {}"));
+        assert!(Parser::is_complete("let a = (1 + 2)"));
+        assert!(Parser::is_complete("let a = * 2;"));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |open: &str, close: &str, depth: usize| {
+            format!("{}1{};", open.repeat(depth), close.repeat(depth))
+        };
+
+        assert!(Parser::new(nested("[", "]", MAX_NESTING_DEPTH - 2))
+            .parse()
+            .is_ok());
+        assert!(Parser::new(nested("{ ", " }", MAX_NESTING_DEPTH - 2))
+            .parse()
+            .is_ok());
+
+        for code in [
+            nested("[", "]", 100_000),
+            nested("(", ")", 100_000),
+            nested("- ", "", 100_000),
+            nested("2 ** ", "", 100_000),
+            nested("a = ", "", 100_000),
+            nested("a ? 1 : ", "", 100_000),
+            nested("{ ", " }", 100_000),
+            nested("if (a) ", "", 100_000),
+        ] {
+            assert_eq!(
+                Parser::new(code).parse().unwrap_err().message,
+                format!("Nesting is deeper than the limit of {}", MAX_NESTING_DEPTH)
+            );
+        }
+    }
+
+    #[test]
+    fn lexer_errors_are_reported() {
+        let mut parser = Parser::new(s!("let a = #;"));
+
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::new("Unexpected character '#'", 1, 9))
+        );
+    }
+
+    #[test]
+    fn break_and_continue_statements() {
+        let mut parser = Parser::new(s!("while (true) { if (a) break; continue; }"));
+        let stmt = parser.parse().unwrap();
+
+        let expected = vec![Statement::_while(
+            Expression::literal(ParserValue::Bool(true)),
+            Statement::_block(vec![
+                Statement::_if(Expression::variable("a"), Statement::_break(), None),
+                Statement::_continue(),
+            ]),
+        )];
+
+        assert_eq!(stmt, expected);
+    }
+
+    #[test]
+    fn labeled_statements() {
+        let mut parser = Parser::new(s!("outer: while (true) { break outer; continue outer; }"));
+        let stmt = parser.parse().unwrap();
+
+        let expected = vec![Statement::_labeled(
+            Ident::new("outer"),
+            Statement::_while(
+                Expression::literal(ParserValue::Bool(true)),
+                Statement::_block(vec![
+                    Statement::_break_to(Ident::new("outer")),
+                    Statement::_continue_to(Ident::new("outer")),
+                ]),
+            ),
+        )];
+
+        assert_eq!(stmt, expected);
+    }
+
+    #[test]
+    fn undefined_labels() {
+        let mut parser = Parser::new(s!("while (true) { break outer; }"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Undefined label 'outer'"
+        );
+
+        // only loops can be continued
+        let mut parser = Parser::new(s!("outer: { while (true) { continue outer; } }"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Undefined label 'outer'"
+        );
+    }
+
+    #[test]
+    fn do_while_statement() {
+        let mut parser = Parser::new(s!("do { a = a + 1; } while (a < 3); a;"));
+        let stmt = parser.parse().unwrap();
+
+        assert_eq!(
+            stmt[0],
+            Statement::_do_while(
+                Statement::_block(vec![Statement::_expression(Expression::assignement(
+                    Ident::new("a"),
+                    Expression::binary(
+                        Expression::variable("a"),
+                        Operator::Plus,
+                        Expression::literal(ParserValue::number("1")),
+                    ),
+                ))]),
+                Expression::binary(
+                    Expression::variable("a"),
+                    Operator::LessThan,
+                    Expression::literal(ParserValue::number("3")),
+                ),
+            )
+        );
+        assert_eq!(stmt.len(), 2);
+    }
+
+    #[test]
+    fn do_while_without_while() {
+        let mut parser = Parser::new(s!("do { break; } (true);"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Expected 'while' after do-while body, got Lparen"
+        );
+    }
+
+    #[test]
+    fn switch_statement() {
+        let mut parser = Parser::new(s!(
+            "switch (a) { case 1: case 2: b = 1; break; default: b = 2; }"
+        ));
+        let stmt = parser.parse().unwrap();
+
+        let assign = |value: &str| {
+            Statement::_expression(Expression::assignement(
+                Ident::new("b"),
+                Expression::literal(ParserValue::number(value)),
+            ))
+        };
+
+        assert_eq!(
+            stmt,
+            vec![Statement::_switch(
+                Expression::variable("a"),
+                vec![
+                    SwitchCase {
+                        test: Some(Expression::literal(ParserValue::number("1"))),
+                        body: vec![],
+                    },
+                    SwitchCase {
+                        test: Some(Expression::literal(ParserValue::number("2"))),
+                        body: vec![assign("1"), Statement::_break()],
+                    },
+                    SwitchCase {
+                        test: None,
+                        body: vec![assign("2")],
+                    },
+                ],
+            )]
+        );
+    }
+
+    #[test]
+    fn try_statement() {
+        let mut parser = Parser::new(s!(
+            "try { throw \"x\"; } catch (e) { e; } finally { a; } try {} catch {}"
+        ));
+        let stmt = parser.parse().unwrap();
+
+        assert_eq!(
+            stmt,
+            vec![
+                Statement::_try(
+                    BlockStatement::new(vec![Statement::_throw(Expression::literal(
+                        ParserValue::String("x".to_string())
+                    ))]),
+                    Some(CatchClause {
+                        param: Some(Ident::new("e")),
+                        body: BlockStatement::new(vec![Statement::_expression(
+                            Expression::variable("e")
+                        )]),
+                    }),
+                    Some(BlockStatement::new(vec![Statement::_expression(
+                        Expression::variable("a")
+                    )])),
+                ),
+                Statement::_try(
+                    BlockStatement::new(vec![]),
+                    Some(CatchClause {
+                        param: None,
+                        body: BlockStatement::new(vec![]),
+                    }),
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn try_without_catch_or_finally() {
+        let mut parser = Parser::new(s!("try { a; } b;"));
+
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::new(
+                "Expected 'catch' or 'finally' after try block, got Ident(\"b\")",
+                1,
+                12
+            ))
+        );
+    }
+
+    #[test]
+    fn switch_with_two_defaults() {
+        let mut parser = Parser::new(s!("switch (a) { default: default: }"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Cannot have more than one default clause"
+        );
+    }
+
+    #[test]
+    fn continue_inside_switch() {
+        let mut parser = Parser::new(s!("switch (a) { case 1: continue; }"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Cannot use Continue outside of a loop"
+        );
+    }
+
+    #[test]
+    fn for_statement_keeps_increment() {
+        let mut parser = Parser::new(s!("for (let i = 0; i < 2; i = i + 1) continue;"));
+        let stmt = parser.parse().unwrap();
+
+        let expected = vec![Statement::_block(vec![
+            Statement::_let(
+                Ident::new("i"),
+                Some(Expression::literal(ParserValue::number("0"))),
+            ),
+            Statement::_for(
+                vec![Ident::new("i")],
+                Expression::binary(
+                    Expression::variable("i"),
+                    Operator::LessThan,
+                    Expression::literal(ParserValue::number("2")),
+                ),
+                Some(Expression::assignement(
+                    Ident::new("i"),
+                    Expression::binary(
+                        Expression::variable("i"),
+                        Operator::Plus,
+                        Expression::literal(ParserValue::number("1")),
+                    ),
+                )),
+                Statement::_continue(),
+            ),
+        ])];
+
+        assert_eq!(stmt, expected);
+    }
+
+    #[test]
+    fn break_outside_loop() {
+        let mut parser = Parser::new(s!("if (true) { break; }"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Cannot use Break outside of a loop"
+        );
+    }
+
+    #[test]
+    fn continue_inside_function_inside_loop() {
+        let mut parser = Parser::new(s!("while (true) { function f() { continue; } }"));
+
+        assert_eq!(
+            parser.parse().unwrap_err().message,
+            "Cannot use Continue outside of a loop"
+        );
+    }
+
     #[test]
     fn function_with_closures() {
         let mut parser = Parser::new(s!("function makeCounter() {
@@ -852,12 +2731,12 @@ mod tests {
             
             function count() {
                 i = i + 1;
-                print i; 
+                i; 
             }
         
             return count;
         }"));
-        let stmt = parser.parse();
+        let stmt = parser.parse().unwrap();
 
         let expected = vec![Statement::function(
             Ident::new("makeCounter"),
@@ -879,7 +2758,7 @@ mod tests {
                                 Expression::literal(ParserValue::number("1")),
                             ),
                         )),
-                        Statement::print(Expression::variable("i")),
+                        Statement::_expression(Expression::variable("i")),
                     ]),
                 ),
                 Statement::_return(Expression::variable("count")),
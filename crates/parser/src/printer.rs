@@ -0,0 +1,820 @@
+use crate::{
+    expression::{Expression, Property},
+    ident::Ident,
+    operator::Operator,
+    statements::{block::BlockStatement, r#let::Pattern, statement::Statement},
+    value::ParserValue,
+};
+
+const INDENT: &str = "    ";
+
+/**
+ * Binding strength of each grammar rule, from the loosest to the tightest. A subexpression that
+ * binds looser than the position it appears in gets wrapped in parentheses
+ */
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Precedence {
+    Assignment,
+    Ternary,
+    Nullish,
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equality,
+    Comparison,
+    Shift,
+    Term,
+    Factor,
+    Power,
+    Unary,
+    Call,
+    Primary,
+}
+
+/**
+ * Prints a whole program, one top-level statement per line
+ */
+pub fn to_source(statements: &[Statement]) -> String {
+    let mut printer = Printer::new();
+
+    for statement in statements {
+        printer.statement(statement);
+    }
+
+    return printer.output;
+}
+
+/**
+ * Turns an AST back into source code that parses to the same AST
+ */
+pub struct Printer {
+    output: String,
+    indent: usize,
+}
+
+impl Printer {
+    pub fn new() -> Printer {
+        Printer {
+            output: String::new(),
+            indent: 0,
+        }
+    }
+
+    pub fn finish(self) -> String {
+        return self.output;
+    }
+
+    /**
+     * Prints `statement` on its own line(s) at the current indentation
+     */
+    pub fn statement(&mut self, statement: &Statement) {
+        self.write_indent();
+        self.statement_inline(statement);
+        self.output.push('\n');
+    }
+
+    pub fn expression(&mut self, expression: &Expression) {
+        self.expression_at(expression, Precedence::Assignment);
+    }
+
+    fn statement_inline(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let(stmt) => {
+                self.output
+                    .push_str(if stmt.constant { "const " } else { "let " });
+
+                for (i, (pattern, expression)) in stmt.declarations.iter().enumerate() {
+                    if i > 0 {
+                        self.output.push_str(", ");
+                    }
+
+                    self.pattern(pattern);
+
+                    if let Some(expression) = expression {
+                        self.output.push_str(" = ");
+                        self.expression(expression);
+                    }
+                }
+
+                self.output.push(';');
+            }
+            Statement::If(stmt) => {
+                self.output.push_str("if (");
+                self.expression(&stmt.condition);
+                self.output.push(')');
+                self.body(&stmt.consequence);
+
+                if let Some(alternative) = &stmt.alternative {
+                    self.before_continuation(&stmt.consequence);
+                    self.output.push_str("else");
+
+                    // keeps else-if chains flat instead of nesting them
+                    if let Statement::If(_) = **alternative {
+                        self.output.push(' ');
+                        self.statement_inline(alternative);
+                    } else {
+                        self.body(alternative);
+                    }
+                }
+            }
+            Statement::While(stmt) => match &stmt.increment {
+                Some(increment) => {
+                    self.output.push_str("for (; ");
+                    self.expression(&stmt.condition);
+                    self.output.push_str("; ");
+                    self.expression(increment);
+                    self.output.push(')');
+                    self.body(&stmt.body);
+                }
+                None => {
+                    self.output.push_str("while (");
+                    self.expression(&stmt.condition);
+                    self.output.push(')');
+                    self.body(&stmt.body);
+                }
+            },
+            Statement::DoWhile(stmt) => {
+                self.output.push_str("do");
+                self.body(&stmt.body);
+                self.before_continuation(&stmt.body);
+                self.output.push_str("while (");
+                self.expression(&stmt.condition);
+                self.output.push_str(");");
+            }
+            Statement::Switch(stmt) => {
+                self.output.push_str("switch (");
+                self.expression(&stmt.discriminant);
+                self.output.push_str(") {\n");
+                self.indent += 1;
+
+                for case in &stmt.cases {
+                    self.write_indent();
+
+                    match &case.test {
+                        Some(test) => {
+                            self.output.push_str("case ");
+                            self.expression(test);
+                            self.output.push_str(":\n");
+                        }
+                        None => self.output.push_str("default:\n"),
+                    }
+
+                    self.indent += 1;
+
+                    for statement in &case.body {
+                        self.statement(statement);
+                    }
+
+                    self.indent -= 1;
+                }
+
+                self.indent -= 1;
+                self.write_indent();
+                self.output.push('}');
+            }
+            Statement::Try(stmt) => {
+                self.output.push_str("try ");
+                self.block(&stmt.block);
+
+                if let Some(catch) = &stmt.catch {
+                    self.output.push_str(" catch ");
+
+                    if let Some(param) = &catch.param {
+                        self.output.push('(');
+                        self.ident(param);
+                        self.output.push_str(") ");
+                    }
+
+                    self.block(&catch.body);
+                }
+
+                if let Some(finally) = &stmt.finally {
+                    self.output.push_str(" finally ");
+                    self.block(finally);
+                }
+            }
+            Statement::Throw(expression) => {
+                self.output.push_str("throw ");
+                self.expression(expression);
+                self.output.push(';');
+            }
+            Statement::Block(block) => {
+                if !self.for_loop(block) {
+                    self.block(block);
+                }
+            }
+            Statement::Expression(expression) => {
+                self.expression(expression);
+                self.output.push(';');
+            }
+            Statement::Function(stmt) => {
+                self.output.push_str("function ");
+                self.ident(&stmt.ident);
+                self.function(&stmt.parameters, &stmt.body);
+            }
+            Statement::Return(Some(expression)) => {
+                self.output.push_str("return ");
+                self.expression(expression);
+                self.output.push(';');
+            }
+            Statement::Return(None) => self.output.push_str("return;"),
+            Statement::Labeled(stmt) => {
+                self.ident(&stmt.label);
+                self.output.push_str(": ");
+                self.statement_inline(&stmt.body);
+            }
+            Statement::Break(label) => self.loop_control("break", label),
+            Statement::Continue(label) => self.loop_control("continue", label),
+        }
+    }
+
+    fn loop_control(&mut self, keyword: &str, label: &Option<Ident>) {
+        self.output.push_str(keyword);
+
+        if let Some(label) = label {
+            self.output.push(' ');
+            self.ident(label);
+        }
+
+        self.output.push(';');
+    }
+
+    /**
+     * The parser turns `for (init; condition; increment)` into a block holding the initializer and
+     * the loop, prints that shape back as a for loop
+     */
+    fn for_loop(&mut self, block: &BlockStatement) -> bool {
+        let [initializer, Statement::While(stmt)] = block.statements().as_slice() else {
+            return false;
+        };
+
+        let Some(increment) = &stmt.increment else {
+            return false;
+        };
+
+        match initializer {
+            Statement::Let(declaration) if !declaration.constant => {}
+            Statement::Expression(_) => {}
+            _ => return false,
+        }
+
+        self.output.push_str("for (");
+        self.statement_inline(initializer);
+        self.output.push(' ');
+        self.expression(&stmt.condition);
+        self.output.push_str("; ");
+        self.expression(increment);
+        self.output.push(')');
+        self.body(&stmt.body);
+
+        return true;
+    }
+
+    /**
+     * Prints the body of an if or loop, blocks stay on the same line and anything else goes on
+     * the next one, indented
+     */
+    fn body(&mut self, body: &Statement) {
+        if let Statement::Block(block) = body {
+            self.output.push(' ');
+            self.block(block);
+            return;
+        }
+
+        self.output.push('\n');
+        self.indent += 1;
+        self.write_indent();
+        self.statement_inline(body);
+        self.indent -= 1;
+    }
+
+    /**
+     * Separates a body from the `else` or `while` that follows it
+     */
+    fn before_continuation(&mut self, body: &Statement) {
+        if let Statement::Block(_) = body {
+            self.output.push(' ');
+        } else {
+            self.output.push('\n');
+            self.write_indent();
+        }
+    }
+
+    fn block(&mut self, block: &BlockStatement) {
+        if block.statements().is_empty() {
+            self.output.push_str("{}");
+            return;
+        }
+
+        self.output.push_str("{\n");
+        self.indent += 1;
+
+        for statement in block.statements() {
+            self.statement(statement);
+        }
+
+        self.indent -= 1;
+        self.write_indent();
+        self.output.push('}');
+    }
+
+    fn function(&mut self, parameters: &[Ident], body: &BlockStatement) {
+        self.output.push('(');
+
+        for (i, parameter) in parameters.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+
+            self.ident(parameter);
+        }
+
+        self.output.push_str(") ");
+        self.block(body);
+    }
+
+    fn expression_at(&mut self, expression: &Expression, minimum: Precedence) {
+        let parenthesize = precedence(expression) < minimum;
+
+        if parenthesize {
+            self.output.push('(');
+        }
+
+        match expression {
+            Expression::Variable { ident, .. } => self.ident(ident),
+            Expression::This { .. } => self.output.push_str("this"),
+            Expression::Grouping(expression) => {
+                self.output.push('(');
+                self.expression(expression);
+                self.output.push(')');
+            }
+            Expression::Literal(value) => self.literal(value),
+            Expression::Array(elements) => {
+                self.output.push('[');
+                self.list(elements);
+                self.output.push(']');
+            }
+            Expression::Object(properties) => {
+                if properties.is_empty() {
+                    self.output.push_str("{}");
+                } else {
+                    self.output.push_str("{ ");
+
+                    for (i, property) in properties.iter().enumerate() {
+                        if i > 0 {
+                            self.output.push_str(", ");
+                        }
+
+                        match property {
+                            Property::Named(key, value) => {
+                                if is_identifier(key) {
+                                    self.output.push_str(key);
+                                } else {
+                                    self.string(key);
+                                }
+
+                                self.output.push_str(": ");
+                                self.expression(value);
+                            }
+                            Property::Spread(value) => {
+                                self.output.push_str("...");
+                                self.expression(value);
+                            }
+                        }
+                    }
+
+                    self.output.push_str(" }");
+                }
+            }
+            Expression::Spread(expression) => {
+                self.output.push_str("...");
+                self.expression(expression);
+            }
+            Expression::Assignement { ident, value, .. } => {
+                self.ident(ident);
+                self.output.push_str(" = ");
+                self.expression(value);
+            }
+            Expression::Unary { operator, right } => {
+                self.output.push_str(match operator {
+                    Operator::Typeof => "typeof ",
+                    operator => symbol(operator),
+                });
+
+                // keeps `- -x` and `- --x` from lexing as a decrement, and `+ +x` as an increment
+                if starts_with_sign(right, operator) {
+                    self.output.push(' ');
+                }
+
+                self.expression_at(right, Precedence::Unary);
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            }
+            | Expression::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                let precedence = precedence(expression);
+
+                // ** groups to the right, every other binary operator to the left
+                let (left_minimum, right_minimum) = match operator {
+                    Operator::Power => (Precedence::Unary, Precedence::Power),
+                    _ => (precedence, tighter(precedence)),
+                };
+
+                self.expression_at(left, left_minimum);
+                self.output.push(' ');
+                self.output.push_str(symbol(operator));
+                self.output.push(' ');
+                self.expression_at(right, right_minimum);
+            }
+            Expression::Call { callee, arguments } => {
+                self.expression_at(callee, Precedence::Call);
+                self.output.push('(');
+                self.list(arguments);
+                self.output.push(')');
+            }
+            Expression::Get { object, name } => {
+                self.expression_at(object, Precedence::Call);
+                self.output.push('.');
+                self.ident(name);
+            }
+            Expression::Set {
+                object,
+                name,
+                value,
+            } => {
+                self.expression_at(object, Precedence::Call);
+                self.output.push('.');
+                self.ident(name);
+                self.output.push_str(" = ");
+                self.expression(value);
+            }
+            Expression::Index { object, index } => {
+                self.expression_at(object, Precedence::Call);
+                self.output.push('[');
+                self.expression(index);
+                self.output.push(']');
+            }
+            Expression::Update {
+                operator,
+                prefix,
+                target,
+            } => {
+                if *prefix {
+                    self.output.push_str(symbol(operator));
+                    self.expression_at(target, Precedence::Unary);
+                } else {
+                    self.expression_at(target, Precedence::Call);
+                    self.output.push_str(symbol(operator));
+                }
+            }
+            Expression::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expression_at(condition, Precedence::Nullish);
+                self.output.push_str(" ? ");
+                self.expression(then_branch);
+                self.output.push_str(" : ");
+                self.expression(else_branch);
+            }
+        }
+
+        if parenthesize {
+            self.output.push(')');
+        }
+    }
+
+    fn list(&mut self, expressions: &[Expression]) {
+        for (i, expression) in expressions.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+
+            self.expression(expression);
+        }
+    }
+
+    fn literal(&mut self, value: &ParserValue) {
+        match value {
+            ParserValue::String(string) => self.string(string),
+            ParserValue::Number(number) => self.output.push_str(number),
+            ParserValue::Bool(boolean) => self.output.push_str(&boolean.to_string()),
+            ParserValue::Null => self.output.push_str("null"),
+            ParserValue::Function {
+                ident,
+                params,
+                body,
+            } => {
+                self.output.push_str("function");
+
+                if let Some(ident) = ident {
+                    self.output.push(' ');
+                    self.ident(ident);
+                }
+
+                self.function(params, body);
+            }
+        }
+    }
+
+    fn string(&mut self, string: &str) {
+        self.output.push('"');
+
+        for ch in string.chars() {
+            match ch {
+                '"' => self.output.push_str("\\\""),
+                '\\' => self.output.push_str("\\\\"),
+                '\n' => self.output.push_str("\\n"),
+                '\t' => self.output.push_str("\\t"),
+                '\r' => self.output.push_str("\\r"),
+                '\0' => self.output.push_str("\\0"),
+                ch => self.output.push(ch),
+            }
+        }
+
+        self.output.push('"');
+    }
+
+    fn ident(&mut self, ident: &Ident) {
+        self.output.push_str(&ident.value());
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        let (open, close) = match pattern {
+            Pattern::Ident(ident) => return self.ident(ident),
+            Pattern::Array(_) => ("[", "]"),
+            Pattern::Object(idents) if idents.is_empty() => ("{", "}"),
+            Pattern::Object(_) => ("{ ", " }"),
+        };
+
+        self.output.push_str(open);
+
+        for (i, ident) in pattern.idents().iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+
+            self.ident(ident);
+        }
+
+        self.output.push_str(close);
+    }
+
+    fn write_indent(&mut self) {
+        for _ in 0..self.indent {
+            self.output.push_str(INDENT);
+        }
+    }
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn precedence(expression: &Expression) -> Precedence {
+    return match expression {
+        Expression::Assignement { .. } | Expression::Set { .. } | Expression::Spread(_) => {
+            Precedence::Assignment
+        }
+        Expression::Ternary { .. } => Precedence::Ternary,
+        Expression::Logical { operator, .. } | Expression::Binary { operator, .. } => {
+            match operator {
+                Operator::Nullish => Precedence::Nullish,
+                Operator::Or | Operator::LogicalOr => Precedence::Or,
+                Operator::And | Operator::LogicalAnd => Precedence::And,
+                Operator::BitOr => Precedence::BitOr,
+                Operator::BitXor => Precedence::BitXor,
+                Operator::BitAnd => Precedence::BitAnd,
+                Operator::Equal
+                | Operator::NotEqual
+                | Operator::StrictEqual
+                | Operator::StrictNotEqual => Precedence::Equality,
+                Operator::LessThan
+                | Operator::LessThanOrEqual
+                | Operator::GreaterThan
+                | Operator::GreaterThanOrEqual
+                | Operator::In => Precedence::Comparison,
+                Operator::ShiftLeft | Operator::ShiftRight => Precedence::Shift,
+                Operator::Plus | Operator::Minus => Precedence::Term,
+                Operator::Asterisk | Operator::Slash | Operator::Modulo => Precedence::Factor,
+                Operator::Power => Precedence::Power,
+                Operator::Bang
+                | Operator::UnaryPlus
+                | Operator::BitNot
+                | Operator::Typeof
+                | Operator::Increment
+                | Operator::Decrement => Precedence::Unary,
+            }
+        }
+        Expression::Unary { .. } | Expression::Update { .. } => Precedence::Unary,
+        Expression::Call { .. } | Expression::Get { .. } | Expression::Index { .. } => {
+            Precedence::Call
+        }
+        Expression::Variable { .. }
+        | Expression::This { .. }
+        | Expression::Grouping(_)
+        | Expression::Literal(_)
+        | Expression::Array(_)
+        | Expression::Object(_) => Precedence::Primary,
+    };
+}
+
+/**
+ * The next level up, what the right operand of a left-associative operator has to bind at
+ */
+fn tighter(precedence: Precedence) -> Precedence {
+    return match precedence {
+        Precedence::Assignment => Precedence::Ternary,
+        Precedence::Ternary => Precedence::Nullish,
+        Precedence::Nullish => Precedence::Or,
+        Precedence::Or => Precedence::And,
+        Precedence::And => Precedence::BitOr,
+        Precedence::BitOr => Precedence::BitXor,
+        Precedence::BitXor => Precedence::BitAnd,
+        Precedence::BitAnd => Precedence::Equality,
+        Precedence::Equality => Precedence::Comparison,
+        Precedence::Comparison => Precedence::Shift,
+        Precedence::Shift => Precedence::Term,
+        Precedence::Term => Precedence::Factor,
+        Precedence::Factor => Precedence::Power,
+        Precedence::Power => Precedence::Unary,
+        Precedence::Unary => Precedence::Call,
+        Precedence::Call | Precedence::Primary => Precedence::Primary,
+    };
+}
+
+fn symbol(operator: &Operator) -> &'static str {
+    return match operator {
+        Operator::Plus => "+",
+        Operator::Minus => "-",
+        Operator::UnaryPlus => "+",
+        Operator::Asterisk => "*",
+        Operator::Slash => "/",
+        Operator::Modulo => "%",
+        Operator::Power => "**",
+        Operator::Increment => "++",
+        Operator::Decrement => "--",
+        Operator::Equal => "==",
+        Operator::NotEqual => "!=",
+        Operator::StrictEqual => "===",
+        Operator::StrictNotEqual => "!==",
+        Operator::Bang => "!",
+        Operator::Typeof => "typeof",
+        Operator::LogicalAnd | Operator::And => "&&",
+        Operator::LogicalOr | Operator::Or => "||",
+        Operator::Nullish => "??",
+        Operator::LessThan => "<",
+        Operator::LessThanOrEqual => "<=",
+        Operator::GreaterThan => ">",
+        Operator::GreaterThanOrEqual => ">=",
+        Operator::In => "in",
+        Operator::BitAnd => "&",
+        Operator::BitOr => "|",
+        Operator::BitXor => "^",
+        Operator::BitNot => "~",
+        Operator::ShiftLeft => "<<",
+        Operator::ShiftRight => ">>",
+    };
+}
+
+/**
+ * Whether `expression` prints starting with the same sign as the unary `sign` placed before it
+ */
+fn starts_with_sign(expression: &Expression, sign: &Operator) -> bool {
+    let update = match sign {
+        Operator::Minus => Operator::Decrement,
+        Operator::UnaryPlus => Operator::Increment,
+        _ => return false,
+    };
+
+    return match expression {
+        Expression::Unary { operator, .. } => operator == sign,
+        Expression::Update {
+            operator, prefix, ..
+        } => *prefix && *operator == update,
+        _ => false,
+    };
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    return match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        }
+        _ => false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+
+    use super::*;
+
+    fn round_trip(code: &str) -> String {
+        let statements = Parser::new(code).parse().unwrap();
+        let source = to_source(&statements);
+
+        assert_eq!(
+            Parser::new(source.clone()).parse().unwrap(),
+            statements,
+            "printed source parsed differently:\n{}",
+            source
+        );
+
+        return source;
+    }
+
+    #[test]
+    fn round_trip_script() {
+        let source = round_trip(
+            "
+        function fib(n) {
+            if (n < 2) { return n; } else if (n == 2) return 1; else { return fib(n - 1) + fib(n - 2); }
+        }
+
+        let i = 0;
+        while (i < 10) { println(fib(i)); i = i + 1; }",
+        );
+
+        assert_eq!(
+            source,
+            "function fib(n) {
+    if (n < 2) {
+        return n;
+    } else if (n == 2)
+        return 1;
+    else {
+        return fib(n - 1) + fib(n - 2);
+    }
+}
+let i = 0;
+while (i < 10) {
+    println(fib(i));
+    i = i + 1;
+}
+"
+        );
+    }
+
+    #[test]
+    fn round_trip_statements() {
+        round_trip(
+            "
+        const o = { a: 1, \"b c\": [1, 2] };
+        for (let i = 0; i < 2; i = i + 1) continue;
+        outer: for (let i = 0; i < 2; i = i + 1) while (true) { if (i) break outer; continue outer; }
+        do { o.a = o.a + 1; } while (o.a < 5);
+        switch (o.a) { case 5: println(\"five\\n\"); break; default: println(typeof o); }
+        try { throw 1; } catch (e) { println(e); } finally {}
+        try { let f = function(x) { return -x; }; } catch { }
+        let [a, b] = [1, 2], { c, d } = o, [] = [], {} = o;",
+        );
+    }
+
+    #[test]
+    fn round_trip_expressions() {
+        round_trip("println((1 + 2) * 3 - -4 ** 2 ** 3 / 5 % 6);");
+        round_trip("println(a && (b || c) ? f(1)[2].d : !e === (g = 1));");
+        round_trip("println(a | b ^ c & d == e << 1 + 2, ~(a | b) >> 1);");
+        round_trip("println(\"a\" in o == (1 in [1] < 2));");
+        round_trip("println(- -a, - --a, -a--, ++a.b, (a++).c);");
+        round_trip("println(+ +a, + ++a, -+a, +-a, 1 + +a);");
+        round_trip("println(function() { return this.a(this); });");
+        round_trip("println(function() { return; });");
+        round_trip("println((a ?? b) || c, a ?? (b ? c : d), a ?? b ?? c);");
+        round_trip("println([...a, 1, ...[b]], { ...o, \"a b\": 1, ...{} });");
+    }
+
+    #[test]
+    fn parenthesizes_by_precedence() {
+        let expression = Expression::binary(
+            Expression::binary(
+                Expression::variable("a"),
+                Operator::Plus,
+                Expression::variable("b"),
+            ),
+            Operator::Asterisk,
+            Expression::binary(
+                Expression::variable("c"),
+                Operator::Minus,
+                Expression::variable("d"),
+            ),
+        );
+
+        assert_eq!(expression.to_source(), "(a + b) * (c - d)");
+    }
+}
@@ -1,6 +1,7 @@
 use super::statement::Statement;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BlockStatement(Vec<Statement>);
 
 impl BlockStatement {
@@ -11,4 +12,8 @@ impl BlockStatement {
     pub fn statements(&self) -> &Vec<Statement> {
         &self.0
     }
+
+    pub fn statements_mut(&mut self) -> &mut Vec<Statement> {
+        &mut self.0
+    }
 }
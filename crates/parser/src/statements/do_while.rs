@@ -0,0 +1,10 @@
+use crate::expression::Expression;
+
+use super::statement::Statement;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DoWhileStatement {
+    pub body: Box<Statement>,
+    pub condition: Expression,
+}
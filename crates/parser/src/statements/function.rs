@@ -3,6 +3,7 @@ use crate::ident::Ident;
 use super::block::BlockStatement;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FunctionStatement {
     pub ident: Ident,
     pub parameters: Vec<Ident>,
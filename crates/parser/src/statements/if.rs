@@ -3,6 +3,7 @@ use crate::expression::Expression;
 use super::statement::Statement;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct IfStatement {
     pub condition: Expression,
     pub consequence: Box<Statement>,
@@ -0,0 +1,10 @@
+use crate::ident::Ident;
+
+use super::statement::Statement;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LabeledStatement {
+    pub label: Ident,
+    pub body: Box<Statement>,
+}
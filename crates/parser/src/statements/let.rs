@@ -1,7 +1,41 @@
 use crate::{expression::Expression, ident::Ident};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LetStatement {
-    pub ident: Ident,
-    pub expression: Option<Expression>,
+    /// Every declarator of `let a = 1, b;`, defined in order
+    pub declarations: Vec<(Pattern, Option<Expression>)>,
+    /// Set for `const` declarations, whose binding can't be reassigned
+    pub constant: bool,
+}
+
+/**
+ * What a declarator binds its value to
+ */
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Pattern {
+    Ident(Ident),
+    /// `[a, b]`, binds the elements at the same positions
+    Array(Vec<Ident>),
+    /// `{ a, b }`, binds the properties with the same names
+    Object(Vec<Ident>),
+}
+
+impl Pattern {
+    /**
+     * Every name the pattern binds, in order
+     */
+    pub fn idents(&self) -> &[Ident] {
+        match self {
+            Pattern::Ident(ident) => return std::slice::from_ref(ident),
+            Pattern::Array(idents) | Pattern::Object(idents) => return idents,
+        }
+    }
+}
+
+impl From<Ident> for Pattern {
+    fn from(ident: Ident) -> Self {
+        Pattern::Ident(ident)
+    }
 }
@@ -1,6 +1,10 @@
 pub mod block;
+pub mod do_while;
 pub mod function;
 pub mod r#if;
+pub mod labeled;
 pub mod r#let;
 pub mod statement;
+pub mod switch;
+pub mod r#try;
 pub mod r#while;
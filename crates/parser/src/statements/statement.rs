@@ -1,29 +1,66 @@
-use crate::{expression::Expression, ident::Ident};
+use crate::{expression::Expression, ident::Ident, printer::Printer};
 
 use super::{
-    block::BlockStatement, function::FunctionStatement, r#if::IfStatement, r#let::LetStatement,
+    block::BlockStatement,
+    do_while::DoWhileStatement,
+    function::FunctionStatement,
+    labeled::LabeledStatement,
+    r#if::IfStatement,
+    r#let::{LetStatement, Pattern},
+    r#try::{CatchClause, TryStatement},
     r#while::WhileStatement,
+    switch::{SwitchCase, SwitchStatement},
 };
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Statement {
     Let(LetStatement),
     If(IfStatement),
     While(WhileStatement),
+    DoWhile(DoWhileStatement),
+    Switch(SwitchStatement),
+    Try(TryStatement),
+    Throw(Expression),
     Block(BlockStatement),
     Expression(Expression),
-    Print(Expression),
     Function(FunctionStatement),
-    Return(Expression),
+    /// The returned value, `None` for a bare `return;`
+    Return(Option<Expression>),
+    Labeled(LabeledStatement),
+    /// Carries the label of the statement to break out of, the innermost loop or switch when `None`
+    Break(Option<Ident>),
+    /// Carries the label of the loop to continue, the innermost loop when `None`
+    Continue(Option<Ident>),
 }
 
 impl Statement {
-    pub fn _let(ident: Ident, expression: Option<Expression>) -> Self {
-        Self::Let(LetStatement { ident, expression })
+    pub fn _let<P: Into<Pattern>>(pattern: P, expression: Option<Expression>) -> Self {
+        Self::_let_declarations(vec![(pattern, expression)])
     }
 
-    pub fn print(expression: Expression) -> Self {
-        Self::Print(expression)
+    pub fn _let_declarations<P: Into<Pattern>>(declarations: Vec<(P, Option<Expression>)>) -> Self {
+        Self::Let(LetStatement {
+            declarations: declarations
+                .into_iter()
+                .map(|(pattern, expression)| (pattern.into(), expression))
+                .collect(),
+            constant: false,
+        })
+    }
+
+    pub fn _const<P: Into<Pattern>>(pattern: P, expression: Expression) -> Self {
+        Self::_const_declarations(vec![(pattern, expression)])
+    }
+
+    pub fn _const_declarations<P: Into<Pattern>>(declarations: Vec<(P, Expression)>) -> Self {
+        Self::Let(LetStatement {
+            declarations: declarations
+                .into_iter()
+                .map(|(pattern, expression)| (pattern.into(), Some(expression)))
+                .collect(),
+            constant: true,
+        })
     }
 
     pub fn function(ident: Ident, parameters: Vec<Ident>, body: BlockStatement) -> Self {
@@ -35,7 +72,7 @@ impl Statement {
     }
 
     pub fn _return(expression: Expression) -> Self {
-        Self::Return(expression)
+        Self::Return(Some(expression))
     }
 
     pub fn _if(
@@ -58,9 +95,81 @@ impl Statement {
         Self::While(WhileStatement {
             condition,
             body: Box::new(body),
+            increment: None,
+            bindings: vec![],
         })
     }
 
+    pub fn _do_while(body: Statement, condition: Expression) -> Self {
+        Self::DoWhile(DoWhileStatement {
+            body: Box::new(body),
+            condition,
+        })
+    }
+
+    pub fn _switch(discriminant: Expression, cases: Vec<SwitchCase>) -> Self {
+        let default = cases.iter().position(|case| case.test.is_none());
+
+        Self::Switch(SwitchStatement {
+            discriminant,
+            cases,
+            default,
+        })
+    }
+
+    pub fn _try(
+        block: BlockStatement,
+        catch: Option<CatchClause>,
+        finally: Option<BlockStatement>,
+    ) -> Self {
+        Self::Try(TryStatement {
+            block,
+            catch,
+            finally,
+        })
+    }
+
+    pub fn _throw(expression: Expression) -> Self {
+        Self::Throw(expression)
+    }
+
+    pub fn _for(
+        bindings: Vec<Ident>,
+        condition: Expression,
+        increment: Option<Expression>,
+        body: Statement,
+    ) -> Self {
+        Self::While(WhileStatement {
+            condition,
+            body: Box::new(body),
+            increment,
+            bindings,
+        })
+    }
+
+    pub fn _labeled(label: Ident, body: Statement) -> Self {
+        Self::Labeled(LabeledStatement {
+            label,
+            body: Box::new(body),
+        })
+    }
+
+    pub fn _break() -> Self {
+        Self::Break(None)
+    }
+
+    pub fn _break_to(label: Ident) -> Self {
+        Self::Break(Some(label))
+    }
+
+    pub fn _continue() -> Self {
+        Self::Continue(None)
+    }
+
+    pub fn _continue_to(label: Ident) -> Self {
+        Self::Continue(Some(label))
+    }
+
     pub fn _block(statements: Vec<Statement>) -> Self {
         Self::Block(BlockStatement::new(statements))
     }
@@ -68,6 +177,17 @@ impl Statement {
     pub fn _expression(expression: Expression) -> Self {
         Self::Expression(expression)
     }
+
+    /**
+     * Source code that parses back to this statement
+     */
+    pub fn to_source(&self) -> String {
+        let mut printer = Printer::new();
+
+        printer.statement(self);
+
+        return printer.finish();
+    }
 }
 
 #[cfg(test)]
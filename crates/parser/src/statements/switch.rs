@@ -0,0 +1,20 @@
+use crate::expression::Expression;
+
+use super::statement::Statement;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SwitchStatement {
+    pub discriminant: Expression,
+    pub cases: Vec<SwitchCase>,
+    /// Position of the `default` clause in `cases`, execution falls through from there like any case
+    pub default: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SwitchCase {
+    /// `None` for the `default` clause
+    pub test: Option<Expression>,
+    pub body: Vec<Statement>,
+}
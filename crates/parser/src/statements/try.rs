@@ -0,0 +1,19 @@
+use crate::ident::Ident;
+
+use super::block::BlockStatement;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TryStatement {
+    pub block: BlockStatement,
+    pub catch: Option<CatchClause>,
+    pub finally: Option<BlockStatement>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CatchClause {
+    /// Binds the thrown value, `catch { ... }` ignores it
+    pub param: Option<Ident>,
+    pub body: BlockStatement,
+}
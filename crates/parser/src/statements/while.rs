@@ -1,9 +1,15 @@
-use crate::expression::Expression;
+use crate::{expression::Expression, ident::Ident};
 
 use super::statement::Statement;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WhileStatement {
     pub condition: Expression,
     pub body: Box<Statement>,
+    /// Runs after every iteration, including ones cut short by `continue` (desugared `for` loops)
+    pub increment: Option<Expression>,
+    /// Variables a desugared `for` loop declares in its initializer, every iteration gets its own
+    /// copy of them so closures created in different iterations don't share them
+    pub bindings: Vec<Ident>,
 }
@@ -3,6 +3,7 @@ use core::fmt;
 use crate::{ident::Ident, statements::block::BlockStatement};
 
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ParserValue {
     String(String),
     Number(String),
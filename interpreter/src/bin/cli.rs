@@ -1,16 +1,38 @@
 use std::rc::Rc;
 
-use interpreter::{environment::Environment, interpreter::Interpreter};
+use interpreter::{
+    environment::Environment,
+    error::RuntimeError,
+    interpreter::{with_stack, Interpreter},
+};
 use parser::parser::Parser;
 
 fn main() {
+    // deep recursion in scripts needs more stack than the main thread has
+    with_stack(run);
+}
+
+fn run() {
     let path = std::env::args().nth(1).expect("missing path argument");
     let source = std::fs::read_to_string(path).expect("failed to read file");
     let mut parser = Parser::new(source);
-    let statements = parser.parse();
+    let statements = match parser.parse() {
+        Ok(statements) => statements,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
 
     let environment = Rc::new(Environment::new());
     let mut intepreter = Interpreter::new(statements);
 
-    intepreter.run(&environment);
+    match intepreter.run(&environment) {
+        Ok(_) => {}
+        Err(RuntimeError::Exit(code)) => std::process::exit(code),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
 }
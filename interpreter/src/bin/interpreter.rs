@@ -0,0 +1,43 @@
+use std::io::{self, BufRead, Write};
+
+use interpreter::{error::RuntimeError, interpreter::with_stack, repl::Repl, value::Value};
+
+fn main() {
+    // deep recursion in scripts needs more stack than the main thread has
+    with_stack(run);
+}
+
+fn run() {
+    let mut repl = Repl::new();
+    let stdin = io::stdin();
+    let mut input = String::new();
+
+    loop {
+        // a statement left unfinished keeps reading into the same input
+        print!("{}", if input.is_empty() { "> " } else { "... " });
+        io::stdout().flush().expect("failed to flush stdout");
+
+        let mut line = String::new();
+
+        if stdin
+            .lock()
+            .read_line(&mut line)
+            .expect("failed to read line")
+            == 0
+        {
+            break;
+        }
+
+        input.push_str(&line);
+
+        match repl.eval_line(&input) {
+            Err(RuntimeError::Parse(error)) if error.is_unexpected_eof() => continue,
+            Err(RuntimeError::Exit(code)) => std::process::exit(code),
+            Ok(Value::Null) => {}
+            Ok(value) => println!("{}", value.display()),
+            Err(error) => eprintln!("{}", error),
+        }
+
+        input.clear();
+    }
+}
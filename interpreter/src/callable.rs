@@ -1,18 +1,84 @@
+use std::{
+    any::Any,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
 use dyn_clone::DynClone;
 
-use crate::{interpreter::Interpreter, value::Value};
+use crate::{error::RuntimeError, interpreter::Interpreter, value::Value};
 
 pub trait Callable: DynClone {
     fn name(&self) -> String;
     fn set_name(&mut self, name: String);
-    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Value;
-    fn arity(&self) -> usize;
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError>;
+    /**
+     * Calls the function as a method of `this`, functions that don't use `this` ignore it
+     */
+    fn call_with_this(
+        &self,
+        interpreter: &mut Interpreter,
+        _this: Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        self.call(interpreter, arguments)
+    }
+    fn arity(&self) -> Arity;
+    /**
+     * How the function is shown when printed, its name followed by its parameters when it has
+     * named ones
+     */
+    fn signature(&self) -> String;
+    /**
+     * Lets embedders downcast a function returned by a script to `JsFunction` or `NativeFunction`
+     */
+    fn as_any(&self) -> &dyn Any;
+    /**
+     * Identifies the function object, copies of a value holding the same function share it
+     */
+    fn id(&self) -> usize;
+}
+
+/**
+ * A fresh id for a newly created function object
+ */
+pub fn next_id() -> usize {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    return NEXT_ID.fetch_add(1, Ordering::Relaxed);
+}
+
+/**
+ * How many arguments a function can be called with
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+    Fixed(usize),
+    /// Any number of arguments, including none
+    Variadic,
+}
+
+impl Arity {
+    pub fn check(&self, got: usize) -> Result<(), RuntimeError> {
+        match *self {
+            Arity::Fixed(expected) if expected != got => {
+                Err(RuntimeError::ArityMismatch { expected, got })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 dyn_clone::clone_trait_object!(Callable);
 
+/**
+ * Functions are equal only to themselves, like JS references
+ */
 impl PartialEq for dyn Callable {
     fn eq(&self, other: &Self) -> bool {
-        self.name() == other.name()
+        self.id() == other.id()
     }
 }
@@ -0,0 +1,13 @@
+use crate::value::Value;
+
+/**
+ * Signals a statement can raise to cut the normal, sequential execution short
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlFlow {
+    /// Carries the label being broken out of, `None` targets the innermost loop or switch
+    Break(Option<String>),
+    /// Carries the label of the loop to continue, `None` targets the innermost loop
+    Continue(Option<String>),
+    Return(Value),
+}
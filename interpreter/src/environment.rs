@@ -1,9 +1,18 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use crate::value::Value;
+use crate::{callable::Arity, error::RuntimeError, interpreter::Interpreter, value::Value};
 
 use super::functions::{
-    implementations::{clock, random},
+    implementations::{
+        abs, assert, assert_eq, bool, ceil, clock, clone, div_floor, exit, floor, format, int,
+        json_parse, json_stringify, keys, len, lower, map, max, mod_floor, now, num, pop, pow,
+        print, println, push, random, range, read_file, read_line, round, sleep, split, sqrt, str,
+        trim, upper, values,
+    },
     native_function::NativeFunction,
 };
 
@@ -11,6 +20,7 @@ use super::functions::{
 pub struct Environment {
     enclosing: Option<Rc<Environment>>,
     values: RefCell<HashMap<String, Value>>,
+    constants: RefCell<HashSet<String>>,
 }
 
 impl Clone for Environment {
@@ -18,18 +28,29 @@ impl Clone for Environment {
         Environment {
             enclosing: None,
             values: self.values.clone(),
+            constants: self.constants.clone(),
         }
     }
 }
 
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Environment {
     pub fn new() -> Environment {
-        let mut env = Environment {
+        let env = Environment {
             enclosing: None,
             values: RefCell::new(HashMap::new()),
+            constants: RefCell::new(HashSet::new()),
         };
 
-        define_native_functions(&mut env);
+        env.define_constant("undefined", Value::Undefined);
+        env.define_constant("Infinity", Value::Number(f64::INFINITY));
+        env.define_constant("NaN", Value::Number(f64::NAN));
+        define_native_functions(&env);
 
         env
     }
@@ -38,18 +59,30 @@ impl Environment {
         Environment {
             enclosing: Some(Rc::clone(enclosing)),
             values: RefCell::new(HashMap::new()),
+            constants: RefCell::new(HashSet::new()),
         }
     }
 
     pub fn define<S: Into<String>>(&self, name: S, value: Value) {
         let name = name.into();
 
+        self.constants.borrow_mut().remove(&name);
         self.values.borrow_mut().insert(name, value);
     }
 
-    pub fn get(&self, name: &str) -> Value {
+    /**
+     * Defines a binding that `assign` refuses to overwrite
+     */
+    pub fn define_constant<S: Into<String>>(&self, name: S, value: Value) {
+        let name = name.into();
+
+        self.constants.borrow_mut().insert(name.clone());
+        self.values.borrow_mut().insert(name, value);
+    }
+
+    pub fn get(&self, name: &str) -> Result<Value, RuntimeError> {
         if let Some(value) = self.values.borrow().get(name) {
-            return value.clone();
+            return Ok(value.clone());
         }
 
         if let Some(enclosing) = &self.enclosing {
@@ -58,28 +91,78 @@ impl Environment {
             }
         }
 
-        panic!("Undefined variable: {}", name);
+        return Err(RuntimeError::UndefinedVariable(name.to_string()));
+    }
+
+    /**
+     * Reads a binding from the scope `distance` levels up, as computed by the resolver, without
+     * searching the scopes in between
+     */
+    pub fn get_at(&self, distance: usize, name: &str) -> Result<Value, RuntimeError> {
+        return match self.ancestor(distance).values.borrow().get(name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(RuntimeError::UndefinedVariable(name.to_string())),
+        };
     }
 
-    pub fn assign(&self, name: &str, value: Value) {
+    pub fn assign_at(&self, distance: usize, name: &str, value: Value) -> Result<(), RuntimeError> {
+        let scope = self.ancestor(distance);
+
+        if !scope.values.borrow().contains_key(name) {
+            return Err(RuntimeError::UndefinedVariable(name.to_string()));
+        }
+
+        return scope.assign(name, value);
+    }
+
+    fn ancestor(&self, distance: usize) -> &Environment {
+        let mut environment = self;
+
+        for _ in 0..distance {
+            environment = environment
+                .enclosing
+                .as_ref()
+                .expect("Resolved scope distance is deeper than the environment chain");
+        }
+
+        return environment;
+    }
+
+    /**
+     * The outermost scope, where variables the resolver left unresolved live
+     */
+    pub fn globals(&self) -> &Environment {
+        let mut environment = self;
+
+        while let Some(enclosing) = &environment.enclosing {
+            environment = enclosing;
+        }
+
+        return environment;
+    }
+
+    pub fn assign(&self, name: &str, value: Value) -> Result<(), RuntimeError> {
         let mut values = self.values.borrow_mut();
 
-        if let Some(_) = values.get(name) {
+        if values.contains_key(name) {
+            if self.constants.borrow().contains(name) {
+                return Err(RuntimeError::ConstantAssignment(name.to_string()));
+            }
+
             values.insert(name.to_string(), value);
-            return;
+            return Ok(());
         }
 
         if let Some(enclosing) = &self.enclosing {
             if enclosing.has(name) {
-                enclosing.assign(name, value);
-                return;
+                return enclosing.assign(name, value);
             }
         }
 
-        panic!("Undefined variable: {}", name);
+        return Err(RuntimeError::UndefinedVariable(name.to_string()));
     }
     pub fn has(&self, name: &str) -> bool {
-        if let Some(_) = self.values.borrow().get(name) {
+        if self.values.borrow().contains_key(name) {
             return true;
         }
 
@@ -95,29 +178,222 @@ impl Environment {
     pub fn contents(&self) -> HashMap<String, Value> {
         return self.values.borrow().clone();
     }
+
+    /**
+     * Every binding visible from this scope, including the enclosing ones, where a name is
+     * defined more than once the innermost binding wins
+     */
+    pub fn all_bindings(&self) -> HashMap<String, Value> {
+        let mut bindings = match &self.enclosing {
+            Some(enclosing) => enclosing.all_bindings(),
+            None => HashMap::new(),
+        };
+
+        bindings.extend(self.contents());
+
+        return bindings;
+    }
+
+    /**
+     * Defines a host function callable from scripts, closures may capture host state
+     */
+    pub fn register_native<F>(&self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&mut Interpreter, Vec<Value>) -> Result<Value, RuntimeError> + 'static,
+    {
+        self.define(
+            name,
+            Value::Function(Box::new(NativeFunction::new(
+                name,
+                Arity::Fixed(arity),
+                function,
+            ))),
+        );
+    }
+
+    /**
+     * Like `register_native`, for a function that takes any number of arguments
+     */
+    pub fn register_variadic<F>(&self, name: &str, function: F)
+    where
+        F: Fn(&mut Interpreter, Vec<Value>) -> Result<Value, RuntimeError> + 'static,
+    {
+        self.define(
+            name,
+            Value::Function(Box::new(NativeFunction::new(
+                name,
+                Arity::Variadic,
+                function,
+            ))),
+        );
+    }
 }
 
-fn define_native_functions(env: &mut Environment) {
-    env.define(
-        "clock",
-        Value::Function(Box::new(NativeFunction::new("clock", vec![], |_, _| {
-            return clock();
-        }))),
-    );
-
-    env.define(
-        "random",
-        Value::Function(Box::new(NativeFunction::new("random", vec![], |_, _| {
-            return random();
-        }))),
-    );
+fn define_native_functions(env: &Environment) {
+    env.register_native("clock", 0, |_, _| {
+        return clock();
+    });
+
+    env.register_native("now", 0, |_, _| {
+        return now();
+    });
+
+    env.register_native("sleep", 1, |_, arguments| {
+        return sleep(&arguments[0]);
+    });
+
+    env.register_native("random", 0, |_, _| {
+        return random();
+    });
+
+    env.register_native("len", 1, |_, arguments| {
+        return len(&arguments[0]);
+    });
+
+    env.register_native("floor", 1, |_, arguments| {
+        return floor(&arguments[0]);
+    });
+
+    env.register_native("ceil", 1, |_, arguments| {
+        return ceil(&arguments[0]);
+    });
+
+    env.register_native("round", 1, |_, arguments| {
+        return round(&arguments[0]);
+    });
+
+    env.register_native("sqrt", 1, |_, arguments| {
+        return sqrt(&arguments[0]);
+    });
+
+    env.register_native("abs", 1, |_, arguments| {
+        return abs(&arguments[0]);
+    });
+
+    env.register_native("pow", 2, |_, arguments| {
+        return pow(&arguments[0], &arguments[1]);
+    });
+
+    env.register_native("div_floor", 2, |_, arguments| {
+        return div_floor(&arguments[0], &arguments[1]);
+    });
+
+    env.register_native("mod_floor", 2, |_, arguments| {
+        return mod_floor(&arguments[0], &arguments[1]);
+    });
+
+    env.register_variadic("max", |_, arguments| {
+        return max(&arguments);
+    });
+
+    env.register_variadic("range", |_, arguments| {
+        return range(&arguments);
+    });
+
+    env.register_native("str", 1, |_, arguments| {
+        return str(&arguments[0]);
+    });
+
+    env.register_native("num", 1, |_, arguments| {
+        return num(&arguments[0]);
+    });
+
+    env.register_native("bool", 1, |_, arguments| {
+        return bool(&arguments[0]);
+    });
+
+    env.register_native("int", 1, |_, arguments| {
+        return int(&arguments[0]);
+    });
+
+    env.register_native("upper", 1, |_, arguments| {
+        return upper(&arguments[0]);
+    });
+
+    env.register_native("lower", 1, |_, arguments| {
+        return lower(&arguments[0]);
+    });
+
+    env.register_native("trim", 1, |_, arguments| {
+        return trim(&arguments[0]);
+    });
+
+    env.register_native("split", 2, |_, arguments| {
+        return split(&arguments[0], &arguments[1]);
+    });
+
+    env.register_native("push", 2, |_, arguments| {
+        return push(&arguments[0], arguments[1].clone());
+    });
+
+    env.register_native("pop", 1, |_, arguments| {
+        return pop(&arguments[0]);
+    });
+
+    env.register_native("keys", 1, |_, arguments| {
+        return keys(&arguments[0]);
+    });
+
+    env.register_native("values", 1, |_, arguments| {
+        return values(&arguments[0]);
+    });
+
+    env.register_native("clone", 1, |_, arguments| {
+        return clone(&arguments[0]);
+    });
+
+    env.register_native("json_stringify", 1, |_, arguments| {
+        return json_stringify(&arguments[0]);
+    });
+
+    env.register_native("json_parse", 1, |_, arguments| {
+        return json_parse(&arguments[0]);
+    });
+
+    env.register_native("map", 2, |interpreter, arguments| {
+        return map(interpreter, &arguments[0], &arguments[1]);
+    });
+
+    env.register_native("read_line", 0, |interpreter, _| {
+        return read_line(interpreter);
+    });
+
+    env.register_native("read_file", 1, |interpreter, arguments| {
+        return read_file(interpreter, &arguments[0]);
+    });
+
+    env.register_native("exit", 1, |_, arguments| {
+        return exit(&arguments[0]);
+    });
+
+    env.register_native("assert", 1, |_, arguments| {
+        return assert(&arguments[0]);
+    });
+
+    env.register_native("assert_eq", 2, |_, arguments| {
+        return assert_eq(&arguments[0], &arguments[1]);
+    });
+
+    env.register_variadic("format", |_, arguments| {
+        return format(&arguments);
+    });
+
+    env.register_variadic("print", |interpreter, arguments| {
+        return print(interpreter, &arguments);
+    });
+
+    env.register_variadic("println", |interpreter, arguments| {
+        return println(interpreter, &arguments);
+    });
 }
 
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::{cell::RefCell, rc::Rc};
 
-    use crate::value::Value;
+    use parser::parser::Parser;
+
+    use crate::{error::RuntimeError, interpreter::Interpreter, value::Value};
 
     use super::Environment;
 
@@ -128,6 +404,103 @@ mod tests {
 
         outer.define("a", Value::Number(1.0));
 
-        assert_eq!(inner.get("a"), Value::Number(1.0));
+        assert_eq!(inner.get("a"), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn all_bindings() {
+        let outer = Rc::new(Environment::new());
+        let inner = Environment::new_enclosing(&outer);
+
+        outer.define("a", Value::Number(1.0));
+        outer.define("b", Value::Number(2.0));
+        inner.define("a", Value::Number(3.0));
+
+        let bindings = inner.all_bindings();
+
+        assert_eq!(bindings.get("a"), Some(&Value::Number(3.0)));
+        assert_eq!(bindings.get("b"), Some(&Value::Number(2.0)));
+        assert_eq!(bindings.get("undefined"), Some(&Value::Undefined));
+        assert_eq!(outer.all_bindings().get("a"), Some(&Value::Number(1.0)));
+    }
+
+    #[test]
+    fn get_at() {
+        let outer = Rc::new(Environment::new());
+        let middle = Rc::new(Environment::new_enclosing(&outer));
+        let inner = Environment::new_enclosing(&middle);
+
+        outer.define("a", Value::Number(1.0));
+        middle.define("a", Value::Number(2.0));
+
+        assert_eq!(inner.get_at(2, "a"), Ok(Value::Number(1.0)));
+        assert_eq!(inner.get_at(1, "a"), Ok(Value::Number(2.0)));
+        assert_eq!(
+            inner.get_at(0, "a"),
+            Err(RuntimeError::UndefinedVariable("a".to_string()))
+        );
+
+        assert_eq!(inner.assign_at(2, "a", Value::Number(3.0)), Ok(()));
+        assert_eq!(outer.get("a"), Ok(Value::Number(3.0)));
+        assert_eq!(middle.get("a"), Ok(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn constants() {
+        let outer = Rc::new(Environment::new());
+        let inner = Environment::new_enclosing(&outer);
+
+        outer.define_constant("a", Value::Number(1.0));
+
+        assert_eq!(
+            inner.assign("a", Value::Number(2.0)),
+            Err(RuntimeError::ConstantAssignment("a".to_string()))
+        );
+        assert_eq!(outer.get("a"), Ok(Value::Number(1.0)));
+
+        inner.define("a", Value::Number(3.0));
+
+        assert_eq!(inner.assign("a", Value::Number(4.0)), Ok(()));
+    }
+
+    #[test]
+    fn undefined_variable() {
+        let environment = Environment::new();
+
+        assert_eq!(
+            environment.get("a"),
+            Err(RuntimeError::UndefinedVariable("a".to_string()))
+        );
+        assert_eq!(
+            environment.assign("a", Value::Null),
+            Err(RuntimeError::UndefinedVariable("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn register_native() {
+        let environment = Rc::new(Environment::new());
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let captured = Rc::clone(&log);
+
+        environment.register_native("log", 1, move |_, arguments| {
+            captured.borrow_mut().push(arguments[0].clone());
+
+            return Ok(Value::Null);
+        });
+        environment.register_native("double", 1, |_, arguments| {
+            return Ok(Value::Number(arguments[0].to_number()? * 2.0));
+        });
+
+        let statements = Parser::new("log(\"hi\"); let a = double(21); log(a);")
+            .parse()
+            .unwrap();
+        Interpreter::new(statements).run(&environment).unwrap();
+
+        assert_eq!(environment.get("a"), Ok(Value::Number(42.0)));
+        assert_eq!(
+            *log.borrow(),
+            vec![Value::String("hi".to_string()), Value::Number(42.0)]
+        );
     }
 }
@@ -0,0 +1,102 @@
+use core::fmt;
+
+use parser::error::ParseError;
+
+use crate::value::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    Parse(ParseError),
+    UndefinedVariable(String),
+    ConstantAssignment(String),
+    TypeError(String),
+    ArityMismatch {
+        expected: usize,
+        got: usize,
+    },
+    StackOverflow,
+    /// Raised by natives doing integer division, where dividing by zero has no sensible result
+    DivisionByZero,
+    /// Raised once an interpreter created with a budget has run out of steps
+    BudgetExceeded,
+    /// Raised when a single loop runs more iterations than the interpreter allows
+    LoopLimitExceeded(usize),
+    /// Raised by the `assert` and `assert_eq` natives
+    AssertionFailed(String),
+    /// A value raised by `throw` that no `catch` handled
+    Thrown(Value),
+    /// Raised by the `exit` native to stop the script, carrying the exit code it asked for
+    Exit(i32),
+    /// Raised by a native the embedder hasn't allowed to run, carrying its name
+    PermissionDenied(String),
+    /// Raised by natives when the host fails to read or write a file
+    Io(String),
+}
+
+impl RuntimeError {
+    pub fn type_error<S: Into<String>>(message: S) -> Self {
+        RuntimeError::TypeError(message.into())
+    }
+
+    /**
+     * Whether a `catch` clause may handle this error, running out of budget or loop iterations
+     * can't be caught so scripts can't keep themselves alive with a try/catch around their loop,
+     * and `exit` has to reach the host
+     */
+    pub fn is_catchable(&self) -> bool {
+        return !matches!(
+            self,
+            RuntimeError::BudgetExceeded
+                | RuntimeError::LoopLimitExceeded(_)
+                | RuntimeError::Exit(_)
+        );
+    }
+
+    /**
+     * The value a `catch` clause binds for this error, errors raised by the interpreter itself are
+     * caught as their message
+     */
+    pub fn thrown_value(&self) -> Value {
+        match self {
+            RuntimeError::Thrown(value) => value.clone(),
+            error => Value::string(error.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::Parse(error) => write!(f, "{}", error),
+            RuntimeError::UndefinedVariable(name) => write!(f, "Undefined variable: {}", name),
+            RuntimeError::ConstantAssignment(name) => {
+                write!(f, "Assignment to constant variable: {}", name)
+            }
+            RuntimeError::TypeError(message) => write!(f, "TypeError: {}", message),
+            RuntimeError::ArityMismatch { expected, got } => {
+                write!(f, "Expected {} arguments but got {}", expected, got)
+            }
+            RuntimeError::StackOverflow => write!(f, "Maximum call stack size exceeded"),
+            RuntimeError::DivisionByZero => write!(f, "Division by zero"),
+            RuntimeError::BudgetExceeded => write!(f, "Execution budget exceeded"),
+            RuntimeError::LoopLimitExceeded(limit) => {
+                write!(f, "Loop exceeded the limit of {} iterations", limit)
+            }
+            RuntimeError::AssertionFailed(message) => write!(f, "Assertion failed: {}", message),
+            RuntimeError::Thrown(value) => write!(f, "Uncaught {}", value.display()),
+            RuntimeError::Exit(code) => write!(f, "Exited with code {}", code),
+            RuntimeError::PermissionDenied(name) => {
+                write!(f, "Permission denied: {}() needs filesystem access", name)
+            }
+            RuntimeError::Io(message) => write!(f, "IOError: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+impl From<ParseError> for RuntimeError {
+    fn from(error: ParseError) -> Self {
+        RuntimeError::Parse(error)
+    }
+}
@@ -1,16 +1,600 @@
-use crate::value::Value;
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    error::RuntimeError,
+    interpreter::Interpreter,
+    json,
+    properties::Properties,
+    value::{format_number, string_to_number, Value},
+};
 
 use rand::{thread_rng, Rng};
 
-pub fn clock() -> Value {
-    Value::Number(
+pub fn clock() -> Result<Value, RuntimeError> {
+    Ok(Value::Number(
         std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs_f64(),
-    )
+    ))
+}
+
+/**
+ * Whole milliseconds since the epoch
+ */
+pub fn now() -> Result<Value, RuntimeError> {
+    Ok(Value::Number(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as f64,
+    ))
+}
+
+/**
+ * Blocks for `ms` milliseconds, negative and NaN durations don't wait at all and ones too long
+ * to represent, like Infinity, fail
+ */
+pub fn sleep(ms: &Value) -> Result<Value, RuntimeError> {
+    let ms = ms.to_number()?;
+
+    if ms > 0.0 {
+        let duration = std::time::Duration::try_from_secs_f64(ms / 1000.0).map_err(|_| {
+            RuntimeError::type_error(format!("sleep() can't wait {} ms", format_number(ms)))
+        })?;
+
+        std::thread::sleep(duration);
+    }
+
+    Ok(Value::Null)
+}
+
+pub fn random() -> Result<Value, RuntimeError> {
+    Ok(Value::Number(thread_rng().gen_range(0.0..1.0)))
+}
+
+pub fn len(value: &Value) -> Result<Value, RuntimeError> {
+    match value {
+        Value::String(string) => Ok(Value::Number(string.chars().count() as f64)),
+        Value::Array(array) => Ok(Value::Number(array.borrow().len() as f64)),
+        _ => Err(RuntimeError::type_error(format!(
+            "len() expects a string or an array, got {:?}",
+            value
+        ))),
+    }
+}
+
+pub fn floor(x: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(x.to_number()?.floor()))
+}
+
+pub fn ceil(x: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(x.to_number()?.ceil()))
+}
+
+/**
+ * Rounds half-way cases towards positive infinity, like JS's Math.round
+ */
+pub fn round(x: &Value) -> Result<Value, RuntimeError> {
+    let x = x.to_number()?;
+    // comparing the distance instead of adding 0.5 first, which can round up values just below
+    // a half like 0.49999999999999994
+    let floor = x.floor();
+
+    Ok(Value::Number(if x - floor >= 0.5 {
+        floor + 1.0
+    } else {
+        floor
+    }))
+}
+
+pub fn sqrt(x: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(x.to_number()?.sqrt()))
+}
+
+pub fn abs(x: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(x.to_number()?.abs()))
+}
+
+pub fn pow(base: &Value, exponent: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(base.to_number()?.powf(exponent.to_number()?)))
+}
+
+/**
+ * The integer quotient of `a / b`, rounded towards negative infinity
+ */
+pub fn div_floor(a: &Value, b: &Value) -> Result<Value, RuntimeError> {
+    let (a, b) = (a.to_number()?, divisor(b)?);
+
+    Ok(Value::Number((a / b).floor()))
+}
+
+/**
+ * The remainder of dividing `a` by `b`, which unlike `%` is never negative
+ */
+pub fn mod_floor(a: &Value, b: &Value) -> Result<Value, RuntimeError> {
+    let (a, b) = (a.to_number()?, divisor(b)?);
+
+    Ok(Value::Number(a.rem_euclid(b)))
+}
+
+fn divisor(value: &Value) -> Result<f64, RuntimeError> {
+    let divisor = value.to_number()?;
+
+    if divisor == 0.0 {
+        return Err(RuntimeError::DivisionByZero);
+    }
+
+    Ok(divisor)
+}
+
+/**
+ * The largest of any number of numbers, -Infinity when there are none and NaN if any of them is
+ */
+pub fn max(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let mut max = f64::NEG_INFINITY;
+
+    for argument in arguments {
+        let number = argument.to_number()?;
+
+        if number.is_nan() {
+            return Ok(Value::Number(f64::NAN));
+        }
+
+        max = max.max(number);
+    }
+
+    Ok(Value::Number(max))
+}
+
+/**
+ * Converts any value to the string `print` would show for it
+ */
+pub fn str(x: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::String(x.display()))
+}
+
+/**
+ * Converts a value to a boolean with the same rules conditions use
+ */
+pub fn bool(x: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::Bool(x.is_truthy()))
+}
+
+/**
+ * Converts a value to a number, strings that aren't numeric become NaN
+ */
+pub fn num(x: &Value) -> Result<Value, RuntimeError> {
+    let number = match x {
+        Value::Number(number) => *number,
+        Value::Bool(bool) => *bool as u8 as f64,
+        Value::Null => 0.0,
+        Value::String(string) => string_to_number(string),
+        _ => f64::NAN,
+    };
+
+    Ok(Value::Number(number))
+}
+
+/**
+ * Like `num`, dropping the fractional part
+ */
+pub fn int(x: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(num(x)?.to_number()?.trunc()))
+}
+
+pub fn upper(string: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::string(as_string("upper", string)?.to_uppercase()))
+}
+
+pub fn lower(string: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::string(as_string("lower", string)?.to_lowercase()))
+}
+
+/**
+ * Strips whitespace from both ends
+ */
+pub fn trim(string: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::string(as_string("trim", string)?.trim()))
+}
+
+/**
+ * Splits the string around every occurrence of `separator`, an empty separator splits it into
+ * its characters
+ */
+pub fn split(string: &Value, separator: &Value) -> Result<Value, RuntimeError> {
+    let string = as_string("split", string)?;
+    let separator = as_string("split", separator)?;
+
+    let parts = if separator.is_empty() {
+        string
+            .chars()
+            .map(|ch| Value::string(ch.to_string()))
+            .collect()
+    } else {
+        string.split(separator).map(Value::string).collect()
+    };
+
+    Ok(Value::array(parts))
+}
+
+/**
+ * Replaces every `{}` in the template with the next argument, `{{` and `}}` stand for literal
+ * braces. There must be exactly as many arguments as placeholders
+ */
+pub fn format(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let (template, arguments) = match arguments.split_first() {
+        Some((template, arguments)) => (as_string("format", template)?, arguments),
+        None => {
+            return Err(RuntimeError::type_error(
+                "format() expects a template string",
+            ))
+        }
+    };
+
+    let mut formatted = String::new();
+    let mut placeholders = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                formatted.push(ch);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+
+                if let Some(argument) = arguments.get(placeholders) {
+                    formatted.push_str(&argument.display());
+                }
+
+                placeholders += 1;
+            }
+            ('{' | '}', _) => {
+                return Err(RuntimeError::type_error(format!(
+                    "format() found an unmatched '{}', write '{}{}' for a literal brace",
+                    ch, ch, ch
+                )))
+            }
+            _ => formatted.push(ch),
+        }
+    }
+
+    if placeholders != arguments.len() {
+        return Err(RuntimeError::type_error(format!(
+            "format() has {} placeholders but got {} arguments",
+            placeholders,
+            arguments.len()
+        )));
+    }
+
+    Ok(Value::String(formatted))
+}
+
+/**
+ * Most numbers `range` will produce, so a huge count or a tiny step can't exhaust memory
+ */
+const MAX_RANGE_LENGTH: f64 = 10_000_000.0;
+
+/**
+ * The numbers from `start` up to, but not including, `end`, `step` apart. Called as `range(n)`,
+ * `range(start, end)` or `range(start, end, step)`, with `start` defaulting to 0 and `step` to 1
+ */
+pub fn range(arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let numbers = arguments
+        .iter()
+        .map(Value::to_number)
+        .collect::<Result<Vec<f64>, RuntimeError>>()?;
+
+    let (start, end, step) = match numbers[..] {
+        [end] => (0.0, end, 1.0),
+        [start, end] => (start, end, 1.0),
+        [start, end, step] => {
+            // a step that doesn't move towards `end` would never get there
+            if start != end && (step == 0.0 || (step > 0.0) != (end > start)) {
+                return Err(RuntimeError::type_error(format!(
+                    "range() step {} never reaches {} from {}",
+                    format_number(step),
+                    format_number(end),
+                    format_number(start)
+                )));
+            }
+
+            (start, end, step)
+        }
+        _ => {
+            return Err(RuntimeError::type_error(format!(
+                "range() expects 1 to 3 arguments, got {}",
+                arguments.len()
+            )))
+        }
+    };
+
+    if !start.is_finite() || !end.is_finite() {
+        return Err(RuntimeError::type_error("range() expects finite bounds"));
+    }
+
+    let length = ((end - start) / step).ceil().max(0.0);
+
+    // also rejects a NaN step, whose length is NaN
+    if length.is_nan() || length > MAX_RANGE_LENGTH {
+        return Err(RuntimeError::type_error(format!(
+            "range() would produce more than {} numbers",
+            format_number(MAX_RANGE_LENGTH)
+        )));
+    }
+
+    let mut values = Vec::new();
+    let mut value = start;
+
+    while (step > 0.0 && value < end) || (step < 0.0 && value > end) {
+        values.push(Value::Number(value));
+        // multiplying rather than adding up keeps fractional steps from drifting
+        value = start + values.len() as f64 * step;
+    }
+
+    Ok(Value::array(values))
+}
+
+/**
+ * The names of the object's properties, in the order they were added
+ */
+pub fn keys(object: &Value) -> Result<Value, RuntimeError> {
+    match object {
+        Value::Object(object) => Ok(Value::array(
+            object.borrow().keys().map(Value::string).collect(),
+        )),
+        _ => Err(expected_object("keys", object)),
+    }
+}
+
+/**
+ * The values of the object's properties, in the order they were added
+ */
+pub fn values(object: &Value) -> Result<Value, RuntimeError> {
+    match object {
+        Value::Object(object) => Ok(Value::array(object.borrow().values().cloned().collect())),
+        _ => Err(expected_object("values", object)),
+    }
+}
+
+pub fn json_stringify(value: &Value) -> Result<Value, RuntimeError> {
+    Ok(Value::String(json::stringify(value)?))
+}
+
+pub fn json_parse(string: &Value) -> Result<Value, RuntimeError> {
+    json::parse(as_string("json_parse", string)?)
+}
+
+/**
+ * Appends to the array in place, returning its new length
+ */
+pub fn push(array: &Value, value: Value) -> Result<Value, RuntimeError> {
+    match array {
+        Value::Array(array) => {
+            let mut array = array.borrow_mut();
+
+            array.push(value);
+
+            Ok(Value::Number(array.len() as f64))
+        }
+        _ => Err(expected_array("push", array)),
+    }
+}
+
+/**
+ * Removes and returns the last element, or undefined when the array is empty
+ */
+pub fn pop(array: &Value) -> Result<Value, RuntimeError> {
+    match array {
+        Value::Array(array) => Ok(array.borrow_mut().pop().unwrap_or(Value::Undefined)),
+        _ => Err(expected_array("pop", array)),
+    }
+}
+
+/**
+ * Deep-copies arrays and objects, other values are returned as they are. Arrays and objects that
+ * appear several times in `value`, cycles included, are copied once and shared the same way
+ */
+pub fn clone(value: &Value) -> Result<Value, RuntimeError> {
+    Ok(deep_clone(value, &mut HashMap::new()))
+}
+
+fn deep_clone(value: &Value, copies: &mut HashMap<usize, Value>) -> Value {
+    match value {
+        Value::Array(array) => {
+            let key = Rc::as_ptr(array) as usize;
+
+            if let Some(copy) = copies.get(&key) {
+                return copy.clone();
+            }
+
+            let copy = Value::array(Vec::new());
+            copies.insert(key, copy.clone());
+
+            let elements = array
+                .borrow()
+                .iter()
+                .map(|element| deep_clone(element, copies))
+                .collect::<Vec<Value>>();
+
+            if let Value::Array(copied) = &copy {
+                *copied.borrow_mut() = elements;
+            }
+
+            copy
+        }
+        Value::Object(object) => {
+            let key = Rc::as_ptr(object) as usize;
+
+            if let Some(copy) = copies.get(&key) {
+                return copy.clone();
+            }
+
+            let copy = Value::object(Properties::new());
+            copies.insert(key, copy.clone());
+
+            let properties = object
+                .borrow()
+                .iter()
+                .map(|(name, value)| (name.clone(), deep_clone(value, copies)))
+                .collect::<Properties>();
+
+            if let Value::Object(copied) = &copy {
+                *copied.borrow_mut() = properties;
+            }
+
+            copy
+        }
+        _ => value.clone(),
+    }
+}
+
+/**
+ * Calls `function` on every element, collecting the results into a new array
+ */
+pub fn map(
+    interpreter: &mut Interpreter,
+    array: &Value,
+    function: &Value,
+) -> Result<Value, RuntimeError> {
+    // copied so the callback can modify the original array while we go through it
+    let elements = match array {
+        Value::Array(array) => array.borrow().clone(),
+        _ => return Err(expected_array("map", array)),
+    };
+
+    let function = match function {
+        Value::Function(function) => function,
+        _ => {
+            return Err(RuntimeError::type_error(format!(
+                "map() expects a function, got {:?}",
+                function
+            )))
+        }
+    };
+
+    function.arity().check(1)?;
+
+    let mut mapped = Vec::with_capacity(elements.len());
+
+    for element in elements {
+        mapped.push(function.call(interpreter, vec![element])?);
+    }
+
+    Ok(Value::array(mapped))
+}
+
+/**
+ * Writes the arguments to the interpreter's output, separated by spaces
+ */
+pub fn print(interpreter: &mut Interpreter, arguments: &[Value]) -> Result<Value, RuntimeError> {
+    let text = arguments
+        .iter()
+        .map(Value::display)
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    interpreter.write_output(&text)?;
+
+    Ok(Value::Undefined)
+}
+
+/**
+ * Like `print`, ending the line
+ */
+pub fn println(interpreter: &mut Interpreter, arguments: &[Value]) -> Result<Value, RuntimeError> {
+    print(interpreter, arguments)?;
+    interpreter.write_output("\n")?;
+
+    Ok(Value::Undefined)
+}
+
+/**
+ * Reads a line from the interpreter's input, null once it has ended
+ */
+pub fn read_line(interpreter: &mut Interpreter) -> Result<Value, RuntimeError> {
+    Ok(interpreter
+        .read_line()?
+        .map(Value::String)
+        .unwrap_or(Value::Null))
+}
+
+/**
+ * The contents of the file at `path`, only when the interpreter was allowed filesystem access
+ */
+pub fn read_file(interpreter: &mut Interpreter, path: &Value) -> Result<Value, RuntimeError> {
+    if !interpreter.allows_fs() {
+        return Err(RuntimeError::PermissionDenied("read_file".to_string()));
+    }
+
+    let path = as_string("read_file", path)?;
+
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Value::String(contents)),
+        Err(error) => Err(RuntimeError::Io(format!(
+            "Could not read {}: {}",
+            path, error
+        ))),
+    }
+}
+
+/**
+ * Fails unless `condition` is truthy
+ */
+pub fn assert(condition: &Value) -> Result<Value, RuntimeError> {
+    if !condition.is_truthy() {
+        return Err(RuntimeError::AssertionFailed(format!(
+            "{:?} is not truthy",
+            condition
+        )));
+    }
+
+    Ok(Value::Undefined)
+}
+
+/**
+ * Stops the script, `Interpreter::run` hands `code` back to the host as `RuntimeError::Exit`
+ */
+pub fn exit(code: &Value) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Exit(code.to_number()? as i32))
+}
+
+/**
+ * Fails unless the values are loosely equal, like `==`. Arrays and objects are compared by
+ * contents rather than by reference, so separately built but equal structures pass
+ */
+pub fn assert_eq(left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+    let equal = match (left, right) {
+        (Value::Array(_), Value::Array(_)) | (Value::Object(_), Value::Object(_)) => left == right,
+        _ => left.eq(right)?.is_truthy(),
+    };
+
+    if !equal {
+        return Err(RuntimeError::AssertionFailed(format!(
+            "{:?} != {:?}",
+            left, right
+        )));
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn expected_array(name: &str, value: &Value) -> RuntimeError {
+    RuntimeError::type_error(format!("{}() expects an array, got {:?}", name, value))
+}
+
+fn as_string<'a>(name: &str, value: &'a Value) -> Result<&'a str, RuntimeError> {
+    match value {
+        Value::String(string) => Ok(string),
+        _ => Err(RuntimeError::type_error(format!(
+            "{}() expects a string, got {:?}",
+            name, value
+        ))),
+    }
 }
 
-pub fn random() -> Value {
-    Value::Number(thread_rng().gen_range(0.0..1.0))
+fn expected_object(name: &str, value: &Value) -> RuntimeError {
+    RuntimeError::type_error(format!("{}() expects an object, got {:?}", name, value))
 }
@@ -1,8 +1,14 @@
-use std::rc::Rc;
+use std::{any::Any, rc::Rc};
 
 use parser::{ident::Ident, statements::block::BlockStatement};
 
-use crate::{callable::Callable, environment::Environment, interpreter::Interpreter, value::Value};
+use crate::{
+    callable::{next_id, Arity, Callable},
+    environment::Environment,
+    error::RuntimeError,
+    interpreter::Interpreter,
+    value::Value,
+};
 
 #[derive(Debug, Clone)]
 pub struct JsFunction {
@@ -10,9 +16,9 @@ pub struct JsFunction {
     parameters: Vec<Ident>,
     body: BlockStatement,
     closure: Rc<Environment>,
+    id: usize,
 }
 
-#[allow(dead_code)]
 impl JsFunction {
     pub fn new<S: Into<String>>(
         name: Option<S>,
@@ -25,8 +31,13 @@ impl JsFunction {
             parameters,
             body,
             closure,
+            id: next_id(),
         })
     }
+
+    pub fn parameters(&self) -> &[Ident] {
+        return &self.parameters;
+    }
 }
 
 impl Callable for JsFunction {
@@ -36,25 +47,66 @@ impl Callable for JsFunction {
             .unwrap_or_else(|| "<anonymous function>".to_string())
     }
 
-    fn set_name(&mut self, _: String) {
-        unimplemented!()
+    fn set_name(&mut self, name: String) {
+        self.name = Some(name);
+    }
+
+    fn arity(&self) -> Arity {
+        return Arity::Fixed(self.parameters.len());
+    }
+
+    fn signature(&self) -> String {
+        let parameters = self
+            .parameters
+            .iter()
+            .map(Ident::value)
+            .collect::<Vec<String>>();
+
+        // anonymous functions show just their parameters, like `(x)`
+        return format!(
+            "{}({})",
+            self.name.as_deref().unwrap_or_default(),
+            parameters.join(", ")
+        );
     }
 
-    fn arity(&self) -> usize {
-        return self.parameters.len();
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 
-    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Value {
+    fn id(&self) -> usize {
+        return self.id;
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        self.call_with_this(interpreter, Value::Undefined, arguments)
+    }
+
+    fn call_with_this(
+        &self,
+        interpreter: &mut Interpreter,
+        this: Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
         let environment = Rc::new(Environment::new_enclosing(&self.closure)); // TODO: We should pass by reference
 
-        for (parameter, argument) in self.parameters.iter().zip(arguments.into_iter()) {
+        environment.define("this", this);
+
+        for (parameter, argument) in self.parameters.iter().zip(arguments) {
             let ident = parameter.clone();
 
             environment.define(ident.value(), argument);
         }
 
         let body = self.body.clone();
+
+        interpreter.enter_call()?;
         let ret = interpreter.execute_block(body, &environment);
+        interpreter.exit_call();
 
         return ret;
     }
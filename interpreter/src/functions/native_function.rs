@@ -1,24 +1,33 @@
-use parser::ident::Ident;
+use std::{any::Any, rc::Rc};
 
-use crate::{callable::Callable, interpreter::Interpreter, value::Value};
+use crate::{
+    callable::{next_id, Arity, Callable},
+    error::RuntimeError,
+    interpreter::Interpreter,
+    value::Value,
+};
+
+pub type NativeFn = dyn Fn(&mut Interpreter, Vec<Value>) -> Result<Value, RuntimeError>;
 
 #[derive(Clone)]
 pub struct NativeFunction {
     name: String,
-    arguments: Vec<Ident>,
-    function: fn(&mut Interpreter, Vec<Value>) -> Value,
+    arity: Arity,
+    function: Rc<NativeFn>,
+    id: usize,
 }
 
 impl NativeFunction {
-    pub fn new<S: Into<String>>(
-        name: S,
-        arguments: Vec<Ident>,
-        function: fn(&mut Interpreter, Vec<Value>) -> Value,
-    ) -> Self {
+    pub fn new<S, F>(name: S, arity: Arity, function: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&mut Interpreter, Vec<Value>) -> Result<Value, RuntimeError> + 'static,
+    {
         Self {
             name: name.into(),
-            arguments,
-            function,
+            arity,
+            function: Rc::new(function),
+            id: next_id(),
         }
     }
 }
@@ -32,11 +41,27 @@ impl Callable for NativeFunction {
         self.name = name;
     }
 
-    fn call(&self, _interpreter: &mut Interpreter, _arguments: Vec<Value>) -> Value {
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        _arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
         (self.function)(_interpreter, _arguments)
     }
 
-    fn arity(&self) -> usize {
-        self.arguments.len()
+    fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    fn signature(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn id(&self) -> usize {
+        self.id
     }
 }
@@ -1,89 +1,413 @@
-use std::rc::Rc;
+use std::{
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
 
-use crate::{functions::js_function::JsFunction, value::Value};
+use crate::{
+    control_flow::ControlFlow,
+    error::RuntimeError,
+    functions::{implementations, js_function::JsFunction},
+    properties::Properties,
+    resolver::Resolver,
+    value::Value,
+};
 
-use parser::value::ParserValue;
+use parser::{parser::Parser, value::ParserValue};
 
 use super::environment::Environment;
 use parser::{
-    expression::Expression,
+    expression::{Expression, Property},
+    ident::Ident,
     operator::Operator,
-    statements::{block::BlockStatement, function::FunctionStatement, statement::Statement},
+    statements::{
+        block::BlockStatement, do_while::DoWhileStatement, function::FunctionStatement,
+        r#let::Pattern, r#while::WhileStatement, statement::Statement,
+    },
 };
 
+/**
+ * How many nested function calls are allowed before failing with a stack overflow error, each call
+ * recurses through several native frames so this is only safe on a thread with STACK_SIZE of stack
+ */
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+
+/**
+ * Stack size of the thread started by with_stack, large enough to reach DEFAULT_MAX_CALL_DEPTH
+ * with deeply nested expressions in every call even in unoptimized builds
+ */
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/**
+ * Runs f on a new thread with STACK_SIZE of stack and waits for it, panics in f are resumed on the
+ * calling thread
+ */
+pub fn with_stack<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T + Send,
+    T: Send,
+{
+    return std::thread::scope(|scope| {
+        let handle = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .expect("failed to spawn interpreter thread");
+
+        return match handle.join() {
+            Ok(value) => value,
+            Err(panic) => std::panic::resume_unwind(panic),
+        };
+    });
+}
+
 pub struct Interpreter {
     statements: Vec<Statement>,
+    last_value: Value,
+    output: Box<dyn Write>,
+    /// Where `read_line` reads from, stdin when not set
+    input: Option<Box<dyn BufRead>>,
+    call_depth: usize,
+    max_call_depth: usize,
+    budget: Option<usize>,
+    /// How many iterations a single loop may run, unlimited when not set
+    max_loop_iterations: Option<usize>,
+    /// Whether natives may touch the filesystem, off unless the embedder opts in
+    allow_fs: bool,
 }
 
 impl Interpreter {
     pub fn new(statements: Vec<Statement>) -> Interpreter {
-        Interpreter { statements }
+        Interpreter::with_writer(statements, Box::new(io::stdout()))
+    }
+
+    /**
+     * Creates an interpreter whose `print` output goes to `output` instead of stdout
+     */
+    pub fn with_writer(mut statements: Vec<Statement>, output: Box<dyn Write>) -> Interpreter {
+        Resolver::new().resolve(&mut statements);
+
+        Interpreter {
+            statements,
+            last_value: Value::Null,
+            output,
+            input: None,
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            budget: None,
+            max_loop_iterations: None,
+            allow_fs: false,
+        }
+    }
+
+    /**
+     * Creates an interpreter that fails with `RuntimeError::BudgetExceeded` after executing `budget`
+     * statements and expressions, so untrusted scripts can't hang the host
+     */
+    pub fn with_budget(statements: Vec<Statement>, budget: usize) -> Interpreter {
+        let mut interpreter = Interpreter::new(statements);
+        interpreter.budget = Some(budget);
+
+        return interpreter;
+    }
+
+    /**
+     * Makes `read_line` read from `input` instead of stdin
+     */
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = Some(input);
+    }
+
+    /**
+     * Limits how deep script functions may recurse, each call uses native stack so the limit
+     * should stay well below what the host thread's stack can hold
+     */
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    /**
+     * Makes any single `while`, `for` or `do while` loop fail with
+     * `RuntimeError::LoopLimitExceeded` once it starts more than `max_loop_iterations` iterations,
+     * independently of the budget
+     */
+    pub fn set_max_loop_iterations(&mut self, max_loop_iterations: usize) {
+        self.max_loop_iterations = Some(max_loop_iterations);
+    }
+
+    /**
+     * Lets natives like `read_file` access the filesystem, without it they fail with
+     * `RuntimeError::PermissionDenied`. Leave it off for scripts that aren't trusted
+     */
+    pub fn set_allow_fs(&mut self, allow_fs: bool) {
+        self.allow_fs = allow_fs;
+    }
+
+    pub fn allows_fs(&self) -> bool {
+        return self.allow_fs;
+    }
+
+    pub(crate) fn enter_call(&mut self) -> Result<(), RuntimeError> {
+        if self.call_depth >= self.max_call_depth {
+            return Err(RuntimeError::StackOverflow);
+        }
+
+        self.call_depth += 1;
+
+        return Ok(());
+    }
+
+    pub(crate) fn exit_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
+    /**
+     * Counts the start of another iteration of a loop, failing if it goes over the limit
+     */
+    fn loop_iteration(&self, iterations: &mut usize) -> Result<(), RuntimeError> {
+        if let Some(limit) = self.max_loop_iterations {
+            if *iterations >= limit {
+                return Err(RuntimeError::LoopLimitExceeded(limit));
+            }
+        }
+
+        *iterations += 1;
+
+        return Ok(());
+    }
+
+    /**
+     * Spends one step of the budget, if there is one
+     */
+    fn step(&mut self) -> Result<(), RuntimeError> {
+        if let Some(budget) = &mut self.budget {
+            if *budget == 0 {
+                return Err(RuntimeError::BudgetExceeded);
+            }
+
+            *budget -= 1;
+        }
+
+        return Ok(());
     }
 
-    pub fn execute_block(&mut self, block: BlockStatement, environment: &Rc<Environment>) -> Value {
-        let mut return_value = Value::Null;
+    /**
+     * Parses and runs `source` in a fresh global environment, returning the value of the last
+     * top-level expression statement (or null if there was none)
+     */
+    pub fn eval_source(source: &str) -> Result<Value, RuntimeError> {
+        let statements = Parser::new(source).parse()?;
+        let environment = Rc::new(Environment::new());
+
+        return Interpreter::new(statements).run(&environment);
+    }
 
+    /**
+     * Runs a function body, yielding the value of the first `return` reached at any depth
+     */
+    pub fn execute_block(
+        &mut self,
+        block: BlockStatement,
+        environment: &Rc<Environment>,
+    ) -> Result<Value, RuntimeError> {
         for statement in block.statements() {
-            if let Some(value) = self.execute(statement, &environment) {
-                return_value = value;
-                break;
+            if let Some(ControlFlow::Return(value)) = self.execute(statement, environment)? {
+                return Ok(value);
             }
         }
 
-        return return_value;
+        return Ok(Value::Undefined);
     }
 
-    pub fn evaluate(&mut self, expr: &Expression, environment: &Rc<Environment>) -> Value {
-        match expr {
-            Expression::Assignement { ident, value } => {
+    pub fn evaluate(
+        &mut self,
+        expr: &Expression,
+        environment: &Rc<Environment>,
+    ) -> Result<Value, RuntimeError> {
+        self.step()?;
+
+        let value = match expr {
+            Expression::Assignement {
+                ident,
+                value,
+                depth,
+            } => {
                 let name = ident.value();
+                let value = self.evaluate_named(value, &name, environment)?;
 
-                if !environment.has(&name) {
-                    panic!("Undefined variable: {}", name);
-                }
+                assign_variable(environment, &name, *depth, value.clone())?;
+
+                value
+            }
+            Expression::Update {
+                operator,
+                prefix,
+                target,
+            } => {
+                let delta = match operator {
+                    Operator::Increment => 1.0,
+                    _ => -1.0,
+                };
 
-                let mut value = self.evaluate(value, environment);
+                let old = match &**target {
+                    Expression::Variable { ident, depth } => {
+                        let name = ident.value();
+                        let old = lookup_variable(environment, &name, *depth)?.to_number()?;
 
-                if let Value::Function(function) = &mut value {
-                    function.set_name(name.clone())
-                }
+                        assign_variable(environment, &name, *depth, Value::Number(old + delta))?;
+
+                        old
+                    }
+                    Expression::Get { object, name } => {
+                        let object = self.evaluate(object, environment)?;
+                        let old = object.get(&name.value())?.to_number()?;
+
+                        object.set(&name.value(), Value::Number(old + delta))?;
+
+                        old
+                    }
+                    Expression::Index { object, index } => {
+                        let object = self.evaluate(object, environment)?;
+                        let index = self.evaluate(index, environment)?;
+                        let old = object.index(&index)?.to_number()?;
 
-                environment.assign(&name, value.clone());
+                        object.set_index(&index, Value::Number(old + delta))?;
 
-                return value;
+                        old
+                    }
+                    _ => {
+                        return Err(RuntimeError::type_error(format!(
+                            "Invalid update target {:?}",
+                            target
+                        )))
+                    }
+                };
+
+                // prefix forms yield the updated value, postfix ones the value before it
+                if *prefix {
+                    Value::Number(old + delta)
+                } else {
+                    Value::Number(old)
+                }
             }
-            Expression::Binary {
+            Expression::Binary { .. } => self.evaluate_binary(expr, environment)?,
+            Expression::Logical {
                 left,
                 operator,
                 right,
             } => {
-                let left = self.evaluate(&left, environment);
-                let right = self.evaluate(&right, environment);
+                let left = self.evaluate(left, environment)?;
 
+                // the right operand is only evaluated when the left one doesn't decide the result
                 match operator {
-                    Operator::Plus => left.sum(&right),
-                    Operator::Minus => left.sub(&right),
-                    Operator::Asterisk => left.mult(&right),
-                    Operator::Slash => left.div(&right),
-                    Operator::GreaterThan => left.gt(&right),
-                    Operator::GreaterThanOrEqual => left.gte(&right),
-                    Operator::LessThan => left.lt(&right),
-                    Operator::LessThanOrEqual => left.lte(&right),
-                    Operator::Equal => left.eq(&right),
-                    Operator::NotEqual => left.neq(&right),
-                    Operator::And => left.and(&right),
-                    Operator::Or => left.or(&right),
-                    _ => unimplemented!(),
+                    Operator::And if !left.is_truthy() => left,
+                    Operator::Or if left.is_truthy() => left,
+                    Operator::Nullish if !matches!(left, Value::Null | Value::Undefined) => left,
+                    Operator::And | Operator::Or | Operator::Nullish => {
+                        self.evaluate(right, environment)?
+                    }
+                    _ => {
+                        return Err(RuntimeError::type_error(format!(
+                            "Unsupported logical operator {:?}",
+                            operator
+                        )))
+                    }
+                }
+            }
+            Expression::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if self.evaluate(condition, environment)?.is_truthy() {
+                    self.evaluate(then_branch, environment)?
+                } else {
+                    self.evaluate(else_branch, environment)?
+                }
+            }
+            Expression::Array(elements) => {
+                let mut array = Vec::new();
+
+                for element in elements {
+                    match element {
+                        Expression::Spread(spread) => match self.evaluate(spread, environment)? {
+                            Value::Array(values) => array.extend(values.borrow().iter().cloned()),
+                            value => {
+                                return Err(RuntimeError::type_error(format!(
+                                    "Cannot spread {} into an array",
+                                    value.type_of()
+                                )))
+                            }
+                        },
+                        element => array.push(self.evaluate(element, environment)?),
+                    }
                 }
+
+                Value::array(array)
+            }
+            Expression::Object(properties) => {
+                let mut object = Properties::new();
+
+                for property in properties {
+                    match property {
+                        Property::Named(key, value) => {
+                            let value = self.evaluate(value, environment)?;
+
+                            object.insert(key.clone(), value);
+                        }
+                        Property::Spread(value) => match self.evaluate(value, environment)? {
+                            Value::Object(properties) => {
+                                for (key, value) in properties.borrow().iter() {
+                                    object.insert(key.clone(), value.clone());
+                                }
+                            }
+                            value => {
+                                return Err(RuntimeError::type_error(format!(
+                                    "Cannot spread {} into an object",
+                                    value.type_of()
+                                )))
+                            }
+                        },
+                    }
+                }
+
+                Value::object(object)
+            }
+            Expression::Spread(_) => {
+                return Err(RuntimeError::type_error(
+                    "Spread is only allowed inside array and object literals",
+                ))
+            }
+            Expression::Get { object, name } => {
+                let object = self.evaluate(object, environment)?;
+
+                object.get(&name.value())?
+            }
+            // outside of any function there is nothing `this` could refer to
+            Expression::This { depth } => match depth {
+                Some(depth) => environment.get_at(*depth, "this")?,
+                None => Value::Undefined,
+            },
+            Expression::Set {
+                object,
+                name,
+                value,
+            } => {
+                let object = self.evaluate(object, environment)?;
+                let value = self.evaluate(value, environment)?;
+
+                object.set(&name.value(), value.clone())?;
+
+                value
+            }
+            Expression::Index { object, index } => {
+                let object = self.evaluate(object, environment)?;
+                let index = self.evaluate(index, environment)?;
+
+                object.index(&index)?
             }
-            Expression::Grouping(expression) => self.evaluate(&expression, environment),
+            Expression::Grouping(expression) => self.evaluate(expression, environment)?,
             Expression::Literal(value) => match value {
                 ParserValue::String(string) => Value::String(string.clone()),
-                ParserValue::Number(number) => Value::Number(
-                    number
-                        .parse::<f64>()
-                        .expect("Could not parse number from string"),
-                ),
+                ParserValue::Number(number) => Value::Number(parse_number_literal(number)),
                 ParserValue::Bool(boolean) => Value::Bool(*boolean),
                 ParserValue::Null => Value::Null,
                 ParserValue::Function {
@@ -94,87 +418,230 @@ impl Interpreter {
                     ident.clone(),
                     params.clone(),
                     body.clone(),
-                    Rc::clone(&environment),
+                    Rc::clone(environment),
                 )),
             },
             Expression::Unary { operator, right } => {
-                let right = self.evaluate(&right, environment);
+                let value = self.evaluate(right, environment);
+
+                // typeof on an undeclared variable reports "undefined" instead of failing
+                let right = match (operator, &**right, value) {
+                    (
+                        Operator::Typeof,
+                        Expression::Variable { .. },
+                        Err(RuntimeError::UndefinedVariable(_)),
+                    ) => return Ok(Value::string("undefined")),
+                    (_, _, value) => value?,
+                };
 
                 match operator {
-                    Operator::Minus => Value::Number(-right.to_number()),
+                    Operator::Minus => Value::Number(-right.to_number()?),
+                    Operator::UnaryPlus => implementations::num(&right)?,
                     Operator::Bang => Value::Bool(!right.is_truthy()),
-                    _ => unimplemented!(),
+                    Operator::BitNot => right.bit_not()?,
+                    Operator::Typeof => Value::string(right.type_name()),
+                    _ => {
+                        return Err(RuntimeError::type_error(format!(
+                            "Unsupported unary operator {:?}",
+                            operator
+                        )))
+                    }
                 }
             }
-            Expression::Variable(ident) => {
-                let name = ident.value();
-
-                return environment.get(&name).clone();
+            Expression::Variable { ident, depth } => {
+                lookup_variable(environment, &ident.value(), *depth)?
             }
             Expression::Call { callee, arguments } => {
-                let callee = self.evaluate(callee, environment);
+                // calling a property binds `this` to the object it was read from
+                let (callee, this) = match &**callee {
+                    Expression::Get { object, name } => {
+                        let object = self.evaluate(object, environment)?;
+
+                        (object.get(&name.value())?, object)
+                    }
+                    callee => (self.evaluate(callee, environment)?, Value::Undefined),
+                };
 
                 if let Value::Function(function) = callee {
                     let arguments = arguments
-                        .into_iter()
+                        .iter()
                         .map(|argument| self.evaluate(argument, environment))
-                        .collect::<Vec<Value>>();
-
-                    if function.arity() != arguments.len() {
-                        panic!(
-                            "Expected {} arguments but got {}",
-                            function.arity(),
-                            arguments.len()
-                        );
-                    }
+                        .collect::<Result<Vec<Value>, RuntimeError>>()?;
+
+                    function.arity().check(arguments.len())?;
 
-                    return function.call(self, arguments);
+                    function.call_with_this(self, this, arguments)?
                 } else {
-                    panic!("Can only call functions and classes, got {:?}", callee);
+                    return Err(RuntimeError::type_error(format!(
+                        "Can only call functions and classes, got {:?}",
+                        callee
+                    )));
                 }
             }
+        };
+
+        return Ok(value);
+    }
+
+    /**
+     * Evaluates the value bound to `name`, an anonymous function expression takes the name on, as
+     * in `let f = function() {}`
+     */
+    fn evaluate_named(
+        &mut self,
+        expression: &Expression,
+        name: &str,
+        environment: &Rc<Environment>,
+    ) -> Result<Value, RuntimeError> {
+        let mut value = self.evaluate(expression, environment)?;
+
+        if let (
+            Expression::Literal(ParserValue::Function { ident: None, .. }),
+            Value::Function(function),
+        ) = (expression, &mut value)
+        {
+            function.set_name(name.to_string());
         }
+
+        return Ok(value);
     }
 
-    fn execute(&mut self, statement: &Statement, environment: &Rc<Environment>) -> Option<Value> {
+    fn execute(
+        &mut self,
+        statement: &Statement,
+        environment: &Rc<Environment>,
+    ) -> Result<Option<ControlFlow>, RuntimeError> {
+        self.step()?;
+
         match statement {
-            Statement::Print(stmt) => {
-                let value = self.evaluate(stmt, environment);
-                println!("{:?}", value);
-            }
             Statement::Let(stmt) => {
-                let ident = stmt.ident.clone();
-                let name = ident.value();
+                let define = |name: String, value: Value| {
+                    if stmt.constant {
+                        environment.define_constant(name, value);
+                    } else {
+                        environment.define(name, value);
+                    }
+                };
 
-                if let Some(expression) = &stmt.expression {
-                    let value = self.evaluate(&expression, environment);
+                // defined one at a time so later initializers can read earlier ones
+                for (pattern, expression) in &stmt.declarations {
+                    match (pattern, expression) {
+                        (Pattern::Ident(ident), expression) => {
+                            let name = ident.value();
 
-                    environment.define(name, value.clone());
-                } else {
-                    environment.define(name, Value::Null);
-                };
+                            let value = match expression {
+                                Some(expression) => {
+                                    self.evaluate_named(expression, &name, environment)?
+                                }
+                                None => Value::Undefined,
+                            };
+
+                            define(name, value);
+                        }
+                        (Pattern::Array(idents), Some(expression)) => {
+                            let value = self.evaluate(expression, environment)?;
+
+                            for (i, ident) in idents.iter().enumerate() {
+                                define(ident.value(), value.index(&Value::Number(i as f64))?);
+                            }
+                        }
+                        (Pattern::Object(idents), Some(expression)) => {
+                            let value = self.evaluate(expression, environment)?;
+
+                            for ident in idents {
+                                define(ident.value(), value.get(&ident.value())?);
+                            }
+                        }
+                        // the parser requires an initializer for destructuring patterns
+                        (_, None) => {}
+                    }
+                }
             }
             Statement::If(stmt) => {
-                let condition = self.evaluate(&stmt.condition, environment);
+                let condition = self.evaluate(&stmt.condition, environment)?;
 
                 if condition.is_truthy() {
-                    self.execute(&stmt.consequence, environment);
+                    return self.execute(&stmt.consequence, environment);
                 } else if let Some(alternative) = &stmt.alternative {
-                    self.execute(&alternative, environment);
+                    return self.execute(alternative, environment);
                 }
             }
-            Statement::While(stmt) => {
-                while self.evaluate(&stmt.condition, environment).is_truthy() {
-                    self.execute(&stmt.body, environment);
+            Statement::While(stmt) => return self.execute_while(stmt, None, environment),
+            Statement::DoWhile(stmt) => return self.execute_do_while(stmt, None, environment),
+            Statement::Switch(stmt) => {
+                let discriminant = self.evaluate(&stmt.discriminant, environment)?;
+                let mut start = stmt.default;
+
+                for (i, case) in stmt.cases.iter().enumerate() {
+                    if let Some(test) = &case.test {
+                        let test = self.evaluate(test, environment)?;
+
+                        // cases match like ===, as in JS
+                        if discriminant.strict_eq(&test)?.is_truthy() {
+                            start = Some(i);
+                            break;
+                        }
+                    }
+                }
+
+                if let Some(start) = start {
+                    let environment = Rc::new(Environment::new_enclosing(environment));
+
+                    // once a case matches, execution falls through the following ones until a break
+                    for case in &stmt.cases[start..] {
+                        for statement in &case.body {
+                            match self.execute(statement, &environment)? {
+                                Some(ControlFlow::Break(None)) => return Ok(None),
+                                Some(flow) => return Ok(Some(flow)),
+                                None => {}
+                            }
+                        }
+                    }
                 }
             }
             Statement::Block(stmt) => {
-                for statement in stmt.statements() {
-                    self.execute(statement, environment);
+                let environment = Rc::new(Environment::new_enclosing(environment));
+
+                return self.execute_statements(stmt, &environment);
+            }
+            Statement::Try(stmt) => {
+                let scope = Rc::new(Environment::new_enclosing(environment));
+                let result = self.execute_statements(&stmt.block, &scope);
+
+                let result = match (result, &stmt.catch) {
+                    (Err(error), Some(catch)) if error.is_catchable() => {
+                        let scope = Rc::new(Environment::new_enclosing(environment));
+
+                        if let Some(param) = &catch.param {
+                            scope.define(param.value(), error.thrown_value());
+                        }
+
+                        self.execute_statements(&catch.body, &scope)
+                    }
+                    (result, _) => result,
+                };
+
+                // errors no catch may handle skip finally too, so it can't swallow them
+                if matches!(&result, Err(error) if !error.is_catchable()) {
+                    return result;
+                }
+
+                if let Some(finally) = &stmt.finally {
+                    let scope = Rc::new(Environment::new_enclosing(environment));
+
+                    // a finally block that jumps or fails overrides how try/catch completed
+                    if let Some(flow) = self.execute_statements(finally, &scope)? {
+                        return Ok(Some(flow));
+                    }
                 }
+
+                return result;
+            }
+            Statement::Throw(value) => {
+                return Err(RuntimeError::Thrown(self.evaluate(value, environment)?));
             }
             Statement::Expression(stmt) => {
-                Some(self.evaluate(stmt, environment));
+                self.evaluate(stmt, environment)?;
             }
             Statement::Function(FunctionStatement {
                 ident,
@@ -185,33 +652,397 @@ impl Interpreter {
                     Some(ident.clone()),
                     parameters.clone(),
                     body.clone(),
-                    Rc::clone(&environment),
+                    Rc::clone(environment),
                 ));
 
                 environment.define(ident.value(), function);
             }
             Statement::Return(value) => {
-                return Some(self.evaluate(value, environment));
+                let value = match value {
+                    Some(value) => self.evaluate(value, environment)?,
+                    None => Value::Undefined,
+                };
+
+                return Ok(Some(ControlFlow::Return(value)));
+            }
+            Statement::Labeled(stmt) => {
+                let label = stmt.label.value();
+
+                let flow = match &*stmt.body {
+                    Statement::While(body) => {
+                        self.execute_while(body, Some(&label), environment)?
+                    }
+                    Statement::DoWhile(body) => {
+                        self.execute_do_while(body, Some(&label), environment)?
+                    }
+                    // a `for` loop is a block holding its initializer and the loop itself
+                    Statement::Block(block) => match block.statements().as_slice() {
+                        [initializer, Statement::While(body)] => {
+                            let environment = Rc::new(Environment::new_enclosing(environment));
+
+                            match self.execute(initializer, &environment)? {
+                                Some(flow) => Some(flow),
+                                None => self.execute_while(body, Some(&label), &environment)?,
+                            }
+                        }
+                        _ => self.execute(&stmt.body, environment)?,
+                    },
+                    body => self.execute(body, environment)?,
+                };
+
+                return match flow {
+                    Some(ControlFlow::Break(Some(target))) if target == label => Ok(None),
+                    flow => Ok(flow),
+                };
+            }
+            Statement::Break(label) => {
+                return Ok(Some(ControlFlow::Break(label.as_ref().map(Ident::value))))
+            }
+            Statement::Continue(label) => {
+                return Ok(Some(ControlFlow::Continue(
+                    label.as_ref().map(Ident::value),
+                )))
+            }
+        }
+
+        Ok(None)
+    }
+
+    /**
+     * Runs a `while` loop, or a desugared `for` loop. `label` is the label the loop was given, so
+     * `break` and `continue` statements naming it target this loop
+     */
+    fn execute_while(
+        &mut self,
+        stmt: &WhileStatement,
+        label: Option<&str>,
+        environment: &Rc<Environment>,
+    ) -> Result<Option<ControlFlow>, RuntimeError> {
+        let mut iterations = 0;
+        let mut scope = iteration_scope(&stmt.bindings, environment, environment)?;
+
+        while self.evaluate(&stmt.condition, &scope)?.is_truthy() {
+            self.loop_iteration(&mut iterations)?;
+
+            match self.execute(&stmt.body, &scope)? {
+                Some(ControlFlow::Break(target)) if targets(&target, label) => break,
+                Some(ControlFlow::Continue(target)) if targets(&target, label) => {}
+                Some(flow) => return Ok(Some(flow)),
+                None => {}
+            }
+
+            // like JS, the next iteration's copy is made before the increment updates it
+            scope = iteration_scope(&stmt.bindings, environment, &scope)?;
+
+            if let Some(increment) = &stmt.increment {
+                self.evaluate(increment, &scope)?;
+            }
+        }
+
+        return Ok(None);
+    }
+
+    fn execute_do_while(
+        &mut self,
+        stmt: &DoWhileStatement,
+        label: Option<&str>,
+        environment: &Rc<Environment>,
+    ) -> Result<Option<ControlFlow>, RuntimeError> {
+        let mut iterations = 0;
+
+        loop {
+            self.loop_iteration(&mut iterations)?;
+
+            match self.execute(&stmt.body, environment)? {
+                Some(ControlFlow::Break(target)) if targets(&target, label) => break,
+                Some(ControlFlow::Continue(target)) if targets(&target, label) => {}
+                Some(flow) => return Ok(Some(flow)),
+                None => {}
+            }
+
+            if !self.evaluate(&stmt.condition, environment)?.is_truthy() {
+                break;
+            }
+        }
+
+        return Ok(None);
+    }
+
+    fn execute_statements(
+        &mut self,
+        block: &BlockStatement,
+        environment: &Rc<Environment>,
+    ) -> Result<Option<ControlFlow>, RuntimeError> {
+        for statement in block.statements() {
+            if let Some(flow) = self.execute(statement, environment)? {
+                return Ok(Some(flow));
+            }
+        }
+
+        return Ok(None);
+    }
+
+    /**
+     * Writes `text` to where `print` output goes, failing to write is a `RuntimeError::Io`
+     */
+    pub fn write_output(&mut self, text: &str) -> Result<(), RuntimeError> {
+        return self
+            .output
+            .write_all(text.as_bytes())
+            .and_then(|_| self.output.flush())
+            .map_err(|error| RuntimeError::Io(format!("Could not write to output: {}", error)));
+    }
+
+    /**
+     * Reads the next line of input without its line break, or `None` once the input has ended.
+     * Failing to read, or reading something that isn't UTF-8, is a `RuntimeError::Io`
+     */
+    pub fn read_line(&mut self) -> Result<Option<String>, RuntimeError> {
+        let mut line = String::new();
+
+        let read = match &mut self.input {
+            Some(input) => input.read_line(&mut line),
+            None => io::stdin().lock().read_line(&mut line),
+        }
+        .map_err(|error| RuntimeError::Io(format!("Could not read from input: {}", error)))?;
+
+        if read == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+
+        return Ok(Some(line));
+    }
+
+    /**
+     * Evaluates a binary expression together with the binary expressions nested as its left
+     * operand, which is the shape left-associative chains like `a + b + c` parse to. They are
+     * collected into a work stack instead of recursing once per operator, so long generated
+     * chains can't overflow the stack
+     */
+    fn evaluate_binary(
+        &mut self,
+        expr: &Expression,
+        environment: &Rc<Environment>,
+    ) -> Result<Value, RuntimeError> {
+        let mut pending = Vec::new();
+        let mut leftmost = expr;
+
+        while let Expression::Binary {
+            left,
+            operator,
+            right,
+        } = leftmost
+        {
+            // the outermost expression already paid its step in `evaluate`
+            if !pending.is_empty() {
+                self.step()?;
             }
+
+            pending.push((operator, right));
+            leftmost = left;
+        }
+
+        let mut value = self.evaluate(leftmost, environment)?;
+
+        for (operator, right) in pending.into_iter().rev() {
+            let right = self.evaluate(right, environment)?;
+
+            value = apply_binary(operator, &value, &right)?;
         }
 
-        None
+        return Ok(value);
     }
 
-    pub fn run(&mut self, environment: &Rc<Environment>) {
-        let statements = self.statements.clone();
+    /**
+     * Runs every statement, returning the value of the last top-level expression statement. A
+     * top-level `return` stops the run early and returns its value instead
+     */
+    pub fn run(&mut self, environment: &Rc<Environment>) -> Result<Value, RuntimeError> {
+        // taken out rather than cloned, cloning recurses through the whole tree
+        let statements = std::mem::take(&mut self.statements);
+        let result = self.run_statements(&statements, environment);
+
+        self.statements = statements;
+
+        return result;
+    }
 
+    fn run_statements(
+        &mut self,
+        statements: &[Statement],
+        environment: &Rc<Environment>,
+    ) -> Result<Value, RuntimeError> {
         for statement in statements {
-            self.execute(&statement, &environment);
+            if let Statement::Expression(expression) = statement {
+                self.last_value = self.evaluate(expression, environment)?;
+            } else if let Some(ControlFlow::Return(value)) = self.execute(statement, environment)? {
+                // a top-level `return` ends the script, yielding the returned value
+                self.last_value = value;
+
+                break;
+            }
         }
+
+        return Ok(self.last_value.clone());
     }
 }
 
+/**
+ * The scope one iteration of a `for` loop runs in, holding its own copy of the loop variables
+ * taken from `previous`. Loops without loop variables run directly in `environment`
+ */
+fn iteration_scope(
+    bindings: &[Ident],
+    environment: &Rc<Environment>,
+    previous: &Environment,
+) -> Result<Rc<Environment>, RuntimeError> {
+    if bindings.is_empty() {
+        return Ok(Rc::clone(environment));
+    }
+
+    let scope = Rc::new(Environment::new_enclosing(environment));
+
+    for ident in bindings {
+        let name = ident.value();
+
+        scope.define(name.clone(), previous.get(&name)?);
+    }
+
+    return Ok(scope);
+}
+
+/**
+ * Whether a `break` or `continue` naming `target` is aimed at a loop labeled `label`, unlabeled
+ * ones always target the innermost loop
+ */
+fn targets(target: &Option<String>, label: Option<&str>) -> bool {
+    return match target {
+        Some(target) => Some(target.as_str()) == label,
+        None => true,
+    };
+}
+
+fn apply_binary(operator: &Operator, left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+    return match operator {
+        Operator::Plus => left.sum(right),
+        Operator::Minus => left.sub(right),
+        Operator::Asterisk => left.mult(right),
+        Operator::Slash => left.div(right),
+        Operator::Modulo => left.modulo(right),
+        Operator::Power => left.pow(right),
+        Operator::GreaterThan => left.gt(right),
+        Operator::GreaterThanOrEqual => left.gte(right),
+        Operator::LessThan => left.lt(right),
+        Operator::LessThanOrEqual => left.lte(right),
+        Operator::In => right.has(left),
+        Operator::Equal => left.eq(right),
+        Operator::NotEqual => left.neq(right),
+        Operator::StrictEqual => left.strict_eq(right),
+        Operator::StrictNotEqual => left.strict_neq(right),
+        Operator::BitAnd => left.bit_and(right),
+        Operator::BitOr => left.bit_or(right),
+        Operator::BitXor => left.bit_xor(right),
+        Operator::ShiftLeft => left.shl(right),
+        Operator::ShiftRight => left.shr(right),
+        _ => Err(RuntimeError::type_error(format!(
+            "Unsupported binary operator {:?}",
+            operator
+        ))),
+    };
+}
+
+/**
+ * Reads a variable from the scope the resolver found it in, or from the globals when it found none
+ */
+fn lookup_variable(
+    environment: &Environment,
+    name: &str,
+    depth: Option<usize>,
+) -> Result<Value, RuntimeError> {
+    return match depth {
+        Some(depth) => environment.get_at(depth, name),
+        None => environment.globals().get(name),
+    };
+}
+
+fn assign_variable(
+    environment: &Environment,
+    name: &str,
+    depth: Option<usize>,
+    value: Value,
+) -> Result<(), RuntimeError> {
+    return match depth {
+        Some(depth) => environment.assign_at(depth, name, value),
+        None => environment.globals().assign(name, value),
+    };
+}
+
+/**
+ * Converts a number literal as produced by the lexer, including 0x, 0o and 0b prefixed integers
+ */
+fn parse_number_literal(literal: &str) -> f64 {
+    let radix = match literal.get(..2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _ => {
+            return literal
+                .parse::<f64>()
+                .expect("Could not parse number from string")
+        }
+    };
+
+    // folding into an f64 keeps literals wider than 64 bits from overflowing
+    return literal[2..].chars().fold(0.0, |acc, digit| {
+        let digit = digit
+            .to_digit(radix)
+            .expect("Could not parse number from string");
+
+        acc * radix as f64 + digit as f64
+    });
+}
+
 #[cfg(test)]
 mod tests {
-    use parser::parser::Parser;
+    use std::cell::RefCell;
 
     use super::*;
+    use crate::{callable::Arity, functions::native_function::NativeFunction};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn run_with_output(code: &str) -> String {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(code).parse().unwrap();
+        let buffer = SharedBuffer::default();
+
+        let mut interpreter = Interpreter::with_writer(statements, Box::new(buffer.clone()));
+
+        interpreter.run(&environment).unwrap();
+
+        let output = buffer.0.borrow();
+
+        String::from_utf8(output.clone()).expect("Output is not valid UTF-8")
+    }
 
     struct EnvironmentHelper {
         environment: Rc<Environment>,
@@ -219,7 +1050,11 @@ mod tests {
 
     impl EnvironmentHelper {
         fn get(&self, name: &str) -> Value {
-            self.environment.get(name).clone()
+            self.environment.get(name).unwrap()
+        }
+
+        fn has(&self, name: &str) -> bool {
+            self.environment.has(name)
         }
     }
 
@@ -229,43 +1064,1897 @@ mod tests {
 
     fn run_interpreter(code: &str) -> RunResult {
         let environment = Rc::new(Environment::new());
-        let statements = Parser::new(code).parse();
+        let statements = Parser::new(code).parse().unwrap();
 
         let mut interpreter = Interpreter::new(statements);
 
-        interpreter.run(&environment);
+        interpreter.run(&environment).unwrap();
 
         RunResult {
             environment: EnvironmentHelper { environment },
         }
     }
 
-    #[test]
-    fn variable_declaration() {
-        let interpreter = run_interpreter("let x = 1; let y;");
+    fn run_error(code: &str) -> RuntimeError {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(code).parse().unwrap();
+
+        Interpreter::new(statements)
+            .run(&environment)
+            .expect_err("Expected the program to fail")
+    }
+
+    #[test]
+    fn inspect_returned_function() {
+        let value = Interpreter::eval_source("function add(a, b) { return a + b; } add;").unwrap();
+        let function = value.as_callable().expect("Expected a function");
+
+        assert_eq!(function.arity(), Arity::Fixed(2));
+
+        let function = function.as_any().downcast_ref::<JsFunction>().unwrap();
+        let parameters: Vec<_> = function.parameters().iter().map(|p| p.value()).collect();
+
+        assert_eq!(parameters, ["a", "b"]);
+
+        let value = Interpreter::eval_source("upper;").unwrap();
+        let function = value.as_callable().unwrap().as_any();
+
+        assert!(function.downcast_ref::<NativeFunction>().is_some());
+        assert!(Value::number(1).as_callable().is_none());
+    }
+
+    #[test]
+    fn eval_source() {
+        assert_eq!(Interpreter::eval_source("1 + 2 * 3"), Ok(Value::number(7)));
+        assert_eq!(
+            Interpreter::eval_source("let a = 1; a + 1; let b = a;"),
+            Ok(Value::number(2))
+        );
+        assert_eq!(
+            Interpreter::eval_source("function f() { 10; return 1; } f() + 1;"),
+            Ok(Value::number(2))
+        );
+        assert_eq!(Interpreter::eval_source("let a = 1;"), Ok(Value::Null));
+        assert!(matches!(
+            Interpreter::eval_source("let a = ;"),
+            Err(RuntimeError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn run_returns_last_value() {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new("let a = 2; a * 21;").parse().unwrap();
+
+        assert_eq!(
+            Interpreter::new(statements).run(&environment),
+            Ok(Value::Number(42.0))
+        );
+
+        let statements = Parser::new("if (true) { 1; }").parse().unwrap();
+
+        assert_eq!(
+            Interpreter::new(statements).run(&environment),
+            Ok(Value::Null)
+        );
+    }
+
+    #[test]
+    fn print_to_writer() {
+        let output =
+            run_with_output("println(1 + 1); let a = [\"x\"]; println(a); println(\"done\");");
+
+        assert_eq!(output.as_bytes(), b"2\n[x]\ndone\n");
+    }
+
+    #[test]
+    fn print_and_println() {
+        let output =
+            run_with_output("print(\"a\", \"b\"); print(1); println(); println(\"c\", [2], null);");
+
+        assert_eq!(output, "a b1\nc [2] null\n");
+    }
+
+    #[test]
+    fn print_numbers() {
+        let output = run_with_output(
+            "println(1 / 2); println(10 / 2); println(0.1 + 0.2); println(2 * 3.0);",
+        );
+
+        assert_eq!(output, "0.5\n5\n0.30000000000000004\n6\n");
+    }
+
+    #[test]
+    fn variable_declaration() {
+        let interpreter = run_interpreter("let x = 1; let y;");
 
         assert_eq!(interpreter.environment.get("x"), Value::Number(1.0));
-        assert_eq!(interpreter.environment.get("y"), Value::Null);
+        assert_eq!(interpreter.environment.get("y"), Value::Undefined);
+    }
+
+    #[test]
+    fn undefined_is_distinct_from_null() {
+        let output = run_with_output(
+            "
+        let x;
+        println(x);
+        println(typeof x);
+        println(x == null);
+        println(x === null);
+        println(x === undefined);",
+        );
+
+        assert_eq!(output, "undefined\nundefined\ntrue\nfalse\ntrue\n");
+    }
+
+    #[test]
+    fn multiple_declarators() {
+        let interpreter = run_interpreter("let a = 1, b = a + 1, c;");
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(2.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Undefined);
+    }
+
+    #[test]
+    fn destructuring() {
+        let interpreter = run_interpreter(
+            "
+        let [a, b, c] = [1, 2];
+        const { x, missing } = { x: \"x\", y: \"y\" };
+
+        function pair() {
+            let [first, second] = [a, b];
+            return first + second;
+        }
+
+        let d = pair();",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(2.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Undefined);
+        assert_eq!(interpreter.environment.get("x"), Value::string("x"));
+        assert_eq!(interpreter.environment.get("missing"), Value::Undefined);
+        assert_eq!(interpreter.environment.get("d"), Value::Number(3.0));
+        assert_eq!(
+            run_error("const { x } = { x: 1 }; x = 2;"),
+            RuntimeError::ConstantAssignment("x".to_string())
+        );
+    }
+
+    #[test]
+    fn radix_number_literals() {
+        let interpreter =
+            run_interpreter("let a = 0xFF; let b = 0b1010; let c = 0o17; let d = 0x10 + 1;");
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(255.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(10.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Number(15.0));
+        assert_eq!(interpreter.environment.get("d"), Value::Number(17.0));
+    }
+
+    #[test]
+    fn modulo() {
+        let interpreter = run_interpreter("let a = 7 % 3; let b = 10 % 2; let c = 2 * 5 % 3;");
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(0.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Number(1.0));
+    }
+
+    #[test]
+    fn string_concatenation() {
+        let output = run_with_output(
+            "println(\"count: \" + 3); println(1 + \"x\"); println(true + \"!\"); println(1 + 2 + \"3\");",
+        );
+
+        assert_eq!(output, "count: 3\n1x\ntrue!\n33\n");
+    }
+
+    #[test]
+    fn loose_and_strict_equality() {
+        let interpreter = run_interpreter(
+            "
+        let a = 1 == \"1\";
+        let b = 1 === \"1\";
+        let c = null == null;
+        let d = null === null;
+        let e = 1 != \"1\";
+        let f = 1 !== \"1\";
+        let g = [] == [];
+        let arr = [];
+        let h = arr === arr;
+        let i = len == len;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("b"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("c"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("d"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("e"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("f"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("g"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("h"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("i"), Value::Bool(true));
+    }
+
+    #[test]
+    fn functions_are_equal_only_to_themselves() {
+        let interpreter = run_interpreter(
+            "
+        function make() {
+            function counter() {}
+            return counter;
+        }
+
+        let first = make();
+        let copy = first;
+        let a = first === make();
+        let b = first === copy;
+        let c = len === str;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("b"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("c"), Value::Bool(false));
+    }
+
+    #[test]
+    fn power() {
+        let interpreter = run_interpreter(
+            "let a = 2 ** 10; let b = 2 ** 3 ** 2; let c = 2 ** 3 ** 2 == 512; let d = 3 * 2 ** 2; let e = 2 ** 2 * 3; let f = 4 ** 0.5;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1024.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(512.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("d"), Value::Number(12.0));
+        assert_eq!(interpreter.environment.get("e"), Value::Number(12.0));
+        assert_eq!(interpreter.environment.get("f"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        let interpreter = run_interpreter(
+            "let a = 5 & 3; let b = 5 | 3; let c = 5 ^ 3; let d = 1 << 4; let e = ~0; let f = 8 >> 1; let g = -8 >> 1; let h = 1 | 2 << 1;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(7.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Number(6.0));
+        assert_eq!(interpreter.environment.get("d"), Value::Number(16.0));
+        assert_eq!(interpreter.environment.get("e"), Value::Number(-1.0));
+        assert_eq!(interpreter.environment.get("f"), Value::Number(4.0));
+        assert_eq!(interpreter.environment.get("g"), Value::Number(-4.0));
+        assert_eq!(interpreter.environment.get("h"), Value::Number(5.0));
+    }
+
+    #[test]
+    fn update_operators() {
+        let output = run_with_output(
+            "let i = 0; println(i++, i, ++i, i--, --i); let o = { a: 1 }; o.a++; ++o.a; println(o.a);",
+        );
+
+        assert_eq!(output, "0 1 2 2 0\n3\n");
+    }
+
+    #[test]
+    fn update_array_elements() {
+        let output = run_with_output(
+            "
+        let a = [1, [5]];
+        let i = 1;
+        println(a[0]++, a[0], --a[i][0], a);
+
+        let j = 0;
+        a[j++]++;
+        println(a, j);",
+        );
+
+        assert_eq!(output, "1 2 4 [2, [4]]\n[3, [4]] 1\n");
+        assert_eq!(
+            run_error("let a = [1]; a[1]++;"),
+            RuntimeError::type_error("Cannot convert undefined to number")
+        );
+    }
+
+    #[test]
+    fn unary_plus() {
+        let interpreter = run_interpreter(
+            "let a = +\"5\"; let b = +true; let c = +\"x\"; let d = 1 + +\"2\"; let e = +-3;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(5.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(1.0));
+        assert!(matches!(interpreter.environment.get("c"), Value::Number(n) if n.is_nan()));
+        assert_eq!(interpreter.environment.get("d"), Value::Number(3.0));
+        assert_eq!(interpreter.environment.get("e"), Value::Number(-3.0));
+    }
+
+    #[test]
+    fn long_operator_chains() {
+        let chain = vec!["1"; 5000].join(" + ");
+        let interpreter = run_interpreter(&format!(
+            "let a = {}; let b = 2 ** 3 ** 2 - 10 * 2 - 1;",
+            chain
+        ));
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(5000.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(491.0));
+    }
+
+    #[test]
+    fn chained_comparisons() {
+        // like JS, `a < b < c` compares the boolean `a < b` (as 0 or 1) with `c`
+        let interpreter = run_interpreter(
+            "let a = 1 < 2 < 3; let b = 3 > 2 > 1; let c = 3 < 2 < 1; let d = false >= 0; let e = true <= 0;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("b"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("c"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("d"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("e"), Value::Bool(false));
+    }
+
+    #[test]
+    fn in_operator() {
+        let interpreter = run_interpreter(
+            "
+        let o = { a: 1, b: null };
+        let arr = [10, 20];
+        let present = \"b\" in o;
+        let absent = \"c\" in o;
+        let valid = 1 in arr;
+        let invalid = 2 in arr;",
+        );
+
+        assert_eq!(interpreter.environment.get("present"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("absent"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("valid"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("invalid"), Value::Bool(false));
+
+        assert_eq!(
+            run_error("\"a\" in \"abc\";"),
+            RuntimeError::type_error("Cannot use 'in' to search for string in string")
+        );
+    }
+
+    #[test]
+    fn mixed_string_and_number_comparisons() {
+        let interpreter = run_interpreter(
+            "
+        let a = \"5\" > 3;
+        let b = \"abc\" < 1;
+        let c = \"abc\" >= 1;
+        let d = 2 > \"10\";
+        let e = \"2\" > \"10\";
+        let f = 3 <= \"3\";",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("b"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("c"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("d"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("e"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("f"), Value::Bool(true));
+    }
+
+    #[test]
+    fn ternary() {
+        let interpreter = run_interpreter(
+            "let a = 1 < 2 ? \"yes\" : \"no\"; let b = 0 ? 1 : 2; let c = a == \"no\" ? 1 : b == 2 ? 3 : 4;",
+        );
+
+        assert_eq!(
+            interpreter.environment.get("a"),
+            Value::String("yes".to_string())
+        );
+        assert_eq!(interpreter.environment.get("b"), Value::Number(2.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Number(3.0));
+    }
+
+    #[test]
+    fn ternary_skips_untaken_branch() {
+        let interpreter =
+            run_interpreter("let a = true ? 1 : missing(); let b = false ? missing : 2;");
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn logical_short_circuit() {
+        let interpreter = run_interpreter(
+            "
+        let calls = 0;
+
+        function touch() {
+            calls = calls + 1;
+            return true;
+        }
+
+        let a = false && touch();
+        let b = true || touch();
+        let c = true && touch();
+        let d = false || touch();",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("b"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("c"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("d"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("calls"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn logical_operators_return_operands() {
+        let interpreter = run_interpreter(
+            "
+        let a = null || 5;
+        let b = \"x\" && 0;
+        let c = 0 || \"default\";
+        let d = 0 && missing;
+        let e = \"first\" || missing;
+        let f = 1 && 2 && 3;
+        let g = null || 0 || false;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(5.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(0.0));
+        assert_eq!(interpreter.environment.get("c"), Value::string("default"));
+        assert_eq!(interpreter.environment.get("d"), Value::Number(0.0));
+        assert_eq!(interpreter.environment.get("e"), Value::string("first"));
+        assert_eq!(interpreter.environment.get("f"), Value::Number(3.0));
+        assert_eq!(interpreter.environment.get("g"), Value::Bool(false));
+    }
+
+    #[test]
+    fn nullish_coalescing() {
+        let interpreter = run_interpreter(
+            "
+        let a = 0 ?? 5;
+        let b = null ?? 5;
+        let c = undefined ?? \"x\";
+        let d = false ?? missing();
+        let e = \"\" ?? missing();
+        let f = null ?? undefined ?? 3;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(0.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(5.0));
+        assert_eq!(interpreter.environment.get("c"), Value::string("x"));
+        assert_eq!(interpreter.environment.get("d"), Value::Bool(false));
+        assert_eq!(interpreter.environment.get("e"), Value::string(""));
+        assert_eq!(interpreter.environment.get("f"), Value::Number(3.0));
+    }
+
+    #[test]
+    fn break_statement() {
+        let interpreter = run_interpreter(
+            "
+        let i = 0;
+
+        while (true) {
+            if (i == 5) {
+                break;
+            }
+
+            i = i + 1;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("i"), Value::Number(5.0));
+    }
+
+    #[test]
+    fn continue_statement() {
+        let interpreter = run_interpreter(
+            "
+        let sum = 0;
+
+        for (let i = 0; i < 5; i = i + 1) {
+            if (i == 2) continue;
+
+            sum = sum + i;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("sum"), Value::Number(8.0));
+    }
+
+    #[test]
+    fn do_while_runs_body_once() {
+        let interpreter = run_interpreter(
+            "
+        let runs = 0;
+
+        do {
+            runs = runs + 1;
+        } while (false);",
+        );
+
+        assert_eq!(interpreter.environment.get("runs"), Value::Number(1.0));
+    }
+
+    #[test]
+    fn do_while_loop() {
+        let interpreter = run_interpreter(
+            "
+        let i = 0;
+        let sum = 0;
+
+        do {
+            i = i + 1;
+            if (i == 2) continue;
+            if (i == 5) break;
+            sum = sum + i;
+        } while (i < 10);",
+        );
+
+        assert_eq!(interpreter.environment.get("i"), Value::Number(5.0));
+        assert_eq!(interpreter.environment.get("sum"), Value::Number(8.0));
+    }
+
+    #[test]
+    fn switch_matched_case() {
+        let interpreter = run_interpreter(
+            "
+        let result;
+
+        switch (2) {
+            case 1:
+                result = \"one\";
+                break;
+            case 1 + 1:
+                result = \"two\";
+                break;
+            default:
+                result = \"other\";
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("result"), Value::string("two"));
+    }
+
+    #[test]
+    fn switch_fall_through() {
+        let interpreter = run_interpreter(
+            "
+        let calls = 0;
+
+        switch (\"a\") {
+            case \"a\":
+                calls = calls + 1;
+            case \"b\":
+                calls = calls + 10;
+                break;
+            case \"c\":
+                calls = calls + 100;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("calls"), Value::Number(11.0));
+    }
+
+    #[test]
+    fn switch_default() {
+        let interpreter = run_interpreter(
+            "
+        let a;
+        let b;
+
+        switch (5) {
+            case 1:
+                a = 1;
+            default:
+                a = \"default\";
+            case \"5\":
+                b = \"fell through\";
+        }
+
+        let i = 0;
+        let hits = 0;
+
+        while (i < 3) {
+            i = i + 1;
+
+            switch (i) {
+                case 2:
+                    continue;
+                default:
+                    hits = hits + 1;
+            }
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::string("default"));
+        assert_eq!(
+            interpreter.environment.get("b"),
+            Value::string("fell through")
+        );
+        assert_eq!(interpreter.environment.get("hits"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn break_only_exits_innermost_loop() {
+        let interpreter = run_interpreter(
+            "
+        let count = 0;
+
+        for (let i = 0; i < 3; i = i + 1) {
+            while (true) {
+                count = count + 1;
+                break;
+            }
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("count"), Value::Number(3.0));
+    }
+
+    #[test]
+    fn break_label_exits_both_loops() {
+        let interpreter = run_interpreter(
+            "
+        let count = 0;
+        let i = 0;
+
+        outer: while (i < 3) {
+            i = i + 1;
+
+            while (true) {
+                count = count + 1;
+                if (count == 2) break outer;
+            }
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("i"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("count"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn continue_label_skips_to_next_outer_iteration() {
+        let interpreter = run_interpreter(
+            "
+        let count = 0;
+        let skipped = 0;
+
+        outer: for (let i = 0; i < 3; i = i + 1) {
+            for (let j = 0; j < 3; j = j + 1) {
+                count = count + 1;
+                if (j == 1) continue outer;
+            }
+
+            skipped = skipped + 1;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("count"), Value::Number(6.0));
+        assert_eq!(interpreter.environment.get("skipped"), Value::Number(0.0));
+    }
+
+    #[test]
+    fn array_literals() {
+        let interpreter =
+            run_interpreter("let a = [1, 2, 1 + 2]; let b = []; let c = [[1], [a, []]];");
+
+        assert_eq!(
+            interpreter.environment.get("a"),
+            Value::array(vec![Value::number(1), Value::number(2), Value::number(3)])
+        );
+        assert_eq!(interpreter.environment.get("b"), Value::array(vec![]));
+        assert_eq!(
+            interpreter.environment.get("c"),
+            Value::array(vec![
+                Value::array(vec![Value::number(1)]),
+                Value::array(vec![
+                    Value::array(vec![Value::number(1), Value::number(2), Value::number(3)]),
+                    Value::array(vec![]),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn index_access() {
+        let interpreter = run_interpreter(
+            "
+        let a = [10, [20, 30]];
+        let s = \"abc\";
+
+        let first = a[0];
+        let nested = a[1][1];
+        let missing = a[5];
+        let ch = s[1];
+        let computed = a[1 - 1] + a[1][0];",
+        );
+
+        assert_eq!(interpreter.environment.get("first"), Value::number(10));
+        assert_eq!(interpreter.environment.get("nested"), Value::number(30));
+        assert_eq!(interpreter.environment.get("missing"), Value::Undefined);
+        assert_eq!(interpreter.environment.get("ch"), Value::string("b"));
+        assert_eq!(interpreter.environment.get("computed"), Value::number(30));
+    }
+
+    #[test]
+    fn negative_index_access() {
+        assert_eq!(
+            run_error("let a = [1]; let b = a[-1];"),
+            RuntimeError::type_error("Index must be a non-negative integer, got -1")
+        );
+    }
+
+    #[test]
+    fn object_literals() {
+        let interpreter = run_interpreter(
+            "
+        let empty = {};
+        let duplicated = { a: 1, b: 2, a: 3 };
+        let nested = { name: \"x\", \"inner\": { list: [1] } };",
+        );
+
+        assert_eq!(
+            interpreter.environment.get("empty"),
+            Value::object(Properties::new())
+        );
+        assert_eq!(
+            interpreter.environment.get("duplicated"),
+            Value::object(Properties::from([
+                ("a".to_string(), Value::number(3)),
+                ("b".to_string(), Value::number(2)),
+            ]))
+        );
+        assert_eq!(
+            interpreter.environment.get("nested"),
+            Value::object(Properties::from([
+                ("name".to_string(), Value::string("x")),
+                (
+                    "inner".to_string(),
+                    Value::object(Properties::from([(
+                        "list".to_string(),
+                        Value::array(vec![Value::number(1)])
+                    )]))
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn spread_literals() {
+        let interpreter = run_interpreter(
+            "
+        let a = [1, 2];
+        let merged = [0, ...a, ...[3], 4];
+        let defaults = { color: \"red\", size: 1 };
+        let options = { ...defaults, size: 2 };",
+        );
+
+        assert_eq!(
+            interpreter.environment.get("merged"),
+            Value::array(vec![
+                Value::number(0),
+                Value::number(1),
+                Value::number(2),
+                Value::number(3),
+                Value::number(4),
+            ])
+        );
+        assert_eq!(
+            interpreter.environment.get("options"),
+            Value::object(Properties::from([
+                ("color".to_string(), Value::string("red")),
+                ("size".to_string(), Value::number(2)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn spread_of_wrong_type() {
+        assert_eq!(
+            run_error("let a = [...1];"),
+            RuntimeError::type_error("Cannot spread number into an array")
+        );
+        assert_eq!(
+            run_error("let a = { ...[1] };"),
+            RuntimeError::type_error("Cannot spread array into an object")
+        );
+    }
+
+    #[test]
+    fn member_access() {
+        let interpreter = run_interpreter(
+            "
+        let person = { name: \"x\", age: 3, inner: { deep: true } };
+
+        let name = person.name;
+        let missing = person.email;
+        let deep = person.inner.deep;
+
+        person.age = person.age + 1;
+        person.inner.deep = false;
+        let result = person.email = \"x@y\";",
+        );
+
+        assert_eq!(interpreter.environment.get("name"), Value::string("x"));
+        assert_eq!(interpreter.environment.get("missing"), Value::Undefined);
+        assert_eq!(interpreter.environment.get("deep"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("result"), Value::string("x@y"));
+
+        let person = interpreter.environment.get("person");
+
+        assert_eq!(person.get("age"), Ok(Value::number(4)));
+        assert_eq!(
+            person.get("inner").and_then(|inner| inner.get("deep")),
+            Ok(Value::Bool(false))
+        );
+        assert_eq!(person.get("email"), Ok(Value::string("x@y")));
+    }
+
+    #[test]
+    fn calling_function_valued_fields() {
+        let interpreter = run_interpreter(
+            "
+        let math = {
+            double: function(x) { return x * 2; },
+            adder: function(x) { return function(y) { return x + y; }; },
+        };
+
+        let a = math.double(4);
+        let b = math.adder(1)(2);",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(8.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(3.0));
+    }
+
+    #[test]
+    fn methods_see_this() {
+        let interpreter = run_interpreter(
+            "
+        let person = {
+            name: \"Ada\",
+            getName: function() { return this.name; },
+            rename: function(name) { this.name = name; return this; },
+        };
+
+        let name = person.getName();
+        let renamed = person.rename(\"Grace\").getName();
+        let global = typeof this;",
+        );
+
+        assert_eq!(interpreter.environment.get("name"), Value::string("Ada"));
+        assert_eq!(
+            interpreter.environment.get("renamed"),
+            Value::string("Grace")
+        );
+        assert_eq!(
+            run_error("let o = { f: function() { return this.x; } }; let f = o.f; f();"),
+            RuntimeError::type_error("Cannot read property 'x' of non-object undefined")
+        );
+        assert_eq!(
+            interpreter.environment.get("global"),
+            Value::string("undefined")
+        );
+    }
+
+    #[test]
+    fn member_access_on_non_object() {
+        assert_eq!(
+            run_error("let a = 1; let b = a.length;"),
+            RuntimeError::type_error("Cannot read property 'length' of non-object 1")
+        );
+    }
+
+    #[test]
+    fn operations_on_null() {
+        assert_eq!(
+            run_error("null + 1;"),
+            RuntimeError::type_error("Cannot add number to null")
+        );
+        assert_eq!(
+            run_error("null - 1;"),
+            RuntimeError::type_error("Cannot subtract number from null")
+        );
+        assert_eq!(
+            run_error("null * 2;"),
+            RuntimeError::type_error("Cannot multiply null by number")
+        );
+        assert_eq!(
+            run_error("1 < null;"),
+            RuntimeError::type_error("Cannot compare number with null")
+        );
+        assert_eq!(
+            run_error("let a = null; a.x;"),
+            RuntimeError::type_error("Cannot read property 'x' of non-object null")
+        );
+    }
+
+    #[test]
+    fn member_assignment_on_non_object() {
+        assert_eq!(
+            run_error("let a; a.x = 1;"),
+            RuntimeError::type_error("Cannot set property 'x' on non-object undefined")
+        );
+    }
+
+    #[test]
+    fn len_native() {
+        let interpreter = run_interpreter(
+            "
+        let a = [1, 2, [3, 4]];
+        let size = len(a);
+        let empty = len([]);
+        let chars = len(\"héllo\");",
+        );
+
+        assert_eq!(interpreter.environment.get("size"), Value::number(3));
+        assert_eq!(interpreter.environment.get("empty"), Value::number(0));
+        assert_eq!(interpreter.environment.get("chars"), Value::number(5));
+    }
+
+    #[test]
+    fn len_native_on_number() {
+        assert_eq!(
+            run_error("len(42);"),
+            RuntimeError::type_error("len() expects a string or an array, got 42")
+        );
+    }
+
+    #[test]
+    fn math_natives() {
+        let interpreter = run_interpreter(
+            "
+        let a = floor(2.7);
+        let b = floor(-2.2);
+        let c = ceil(2.1);
+        let d = round(2.5);
+        let e = round(-2.5);
+        let almost_half = round(0.49999999999999994);
+        let large = round(4503599627370497);
+        let f = sqrt(16);
+        let g = abs(-3);
+        let h = pow(2, 10);
+        let nan = sqrt(-1);",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::number(2));
+        assert_eq!(interpreter.environment.get("b"), Value::number(-3));
+        assert_eq!(interpreter.environment.get("c"), Value::number(3));
+        assert_eq!(interpreter.environment.get("d"), Value::number(3));
+        assert_eq!(interpreter.environment.get("e"), Value::number(-2));
+        assert_eq!(interpreter.environment.get("almost_half"), Value::number(0));
+        assert_eq!(
+            interpreter.environment.get("large"),
+            Value::Number(4503599627370497.0)
+        );
+        assert_eq!(interpreter.environment.get("f"), Value::number(4));
+        assert_eq!(interpreter.environment.get("g"), Value::number(3));
+        assert_eq!(interpreter.environment.get("h"), Value::number(1024));
+        assert!(interpreter
+            .environment
+            .get("nan")
+            .to_number()
+            .unwrap()
+            .is_nan());
+    }
+
+    #[test]
+    fn timing_natives() {
+        let interpreter = run_interpreter("let a = now(); let b = sleep(0); let c = sleep(-5);");
+
+        assert!(interpreter.environment.get("a").to_number().unwrap() > 0.0);
+        assert_eq!(interpreter.environment.get("b"), Value::Null);
+        assert_eq!(interpreter.environment.get("c"), Value::Null);
+        assert_eq!(
+            run_error("sleep(1 / 0);"),
+            RuntimeError::type_error("sleep() can't wait Infinity ms")
+        );
+        assert_eq!(
+            run_error("sleep(10 ** 300);"),
+            RuntimeError::type_error("sleep() can't wait 1e+300 ms")
+        );
+    }
+
+    #[test]
+    fn floored_division_natives() {
+        let interpreter = run_interpreter(
+            "
+        let a = div_floor(-7, 2);
+        let b = mod_floor(-7, 2);
+        let c = div_floor(7, 2);
+        let d = mod_floor(7, -2);",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::number(-4));
+        assert_eq!(interpreter.environment.get("b"), Value::number(1));
+        assert_eq!(interpreter.environment.get("c"), Value::number(3));
+        assert_eq!(interpreter.environment.get("d"), Value::number(1));
+
+        assert_eq!(run_error("div_floor(1, 0);"), RuntimeError::DivisionByZero);
+        assert_eq!(run_error("mod_floor(1, 0);"), RuntimeError::DivisionByZero);
+    }
+
+    #[test]
+    fn conversion_natives() {
+        let interpreter = run_interpreter(
+            "
+        let a = num(\"3.5\");
+        let b = num(\"abc\");
+        let c = num(\" 12 \");
+        let d = num(\"\");
+        let e = num(true);
+        let f = str(42);
+        let g = str(0.5) + str([1, \"x\"]);
+        let h = int(\"3.9\");
+        let i = int(-3.9);
+        let j = num(\"1e3\");
+        let k = num(\"inf\");",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(3.5));
+        assert!(interpreter
+            .environment
+            .get("b")
+            .to_number()
+            .unwrap()
+            .is_nan());
+        assert_eq!(interpreter.environment.get("c"), Value::Number(12.0));
+        assert_eq!(interpreter.environment.get("d"), Value::Number(0.0));
+        assert_eq!(interpreter.environment.get("e"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("f"), Value::string("42"));
+        assert_eq!(interpreter.environment.get("g"), Value::string("0.5[1, x]"));
+        assert_eq!(interpreter.environment.get("h"), Value::Number(3.0));
+        assert_eq!(interpreter.environment.get("i"), Value::Number(-3.0));
+        assert_eq!(interpreter.environment.get("j"), Value::Number(1000.0));
+        assert!(interpreter
+            .environment
+            .get("k")
+            .to_number()
+            .unwrap()
+            .is_nan());
+    }
+
+    #[test]
+    fn array_natives() {
+        let interpreter = run_interpreter(
+            "
+        let numbers = [];
+        let length = push(numbers, 1);
+        push(numbers, 2);
+        push(numbers, 3);
+
+        function square(n) {
+            return n * n;
+        }
+
+        let squares = map(numbers, square);
+        let last = pop(numbers);
+        let labels = map(squares, function(n) { return \"#\" + n; });
+        let empty = pop([]);",
+        );
+
+        assert_eq!(interpreter.environment.get("length"), Value::Number(1.0));
+        assert_eq!(
+            interpreter.environment.get("squares"),
+            Value::array(vec![
+                Value::Number(1.0),
+                Value::Number(4.0),
+                Value::Number(9.0)
+            ])
+        );
+        assert_eq!(interpreter.environment.get("last"), Value::Number(3.0));
+        assert_eq!(
+            interpreter.environment.get("numbers"),
+            Value::array(vec![Value::Number(1.0), Value::Number(2.0)])
+        );
+        assert_eq!(
+            interpreter.environment.get("labels"),
+            Value::array(vec![
+                Value::string("#1"),
+                Value::string("#4"),
+                Value::string("#9")
+            ])
+        );
+        assert_eq!(interpreter.environment.get("empty"), Value::Undefined);
+    }
+
+    #[test]
+    fn array_natives_type_errors() {
+        assert_eq!(
+            run_error("push(1, 2);"),
+            RuntimeError::type_error("push() expects an array, got 1")
+        );
+        assert_eq!(
+            run_error("map([1], 2);"),
+            RuntimeError::type_error("map() expects a function, got 2")
+        );
+        assert_eq!(
+            run_error("map([1], function(a, b) { return a; });"),
+            RuntimeError::ArityMismatch {
+                expected: 2,
+                got: 1
+            }
+        );
+    }
+
+    #[test]
+    fn math_native_arity() {
+        assert_eq!(
+            run_error("pow(2);"),
+            RuntimeError::ArityMismatch {
+                expected: 2,
+                got: 1
+            }
+        );
+    }
+
+    #[test]
+    fn variadic_native() {
+        let interpreter = run_interpreter(
+            "
+        let a = max(1, 5, 3);
+        let b = max(-2);
+        let c = max();
+        let d = max(1, num(\"x\"));",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(5.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(-2.0));
+        assert_eq!(
+            interpreter.environment.get("c"),
+            Value::Number(f64::NEG_INFINITY)
+        );
+        assert!(interpreter
+            .environment
+            .get("d")
+            .to_number()
+            .unwrap()
+            .is_nan());
+        assert_eq!(
+            run_error("max(1, \"2\");"),
+            RuntimeError::type_error("Cannot convert \"2\" to number")
+        );
+    }
+
+    #[test]
+    fn range_native() {
+        let interpreter = run_interpreter(
+            "
+        let a = range(4);
+        let b = range(2, 5);
+        let c = range(0, 10, 3);
+        let d = range(5, 0, -2);
+        let e = range(3, 3, 0);",
+        );
+
+        let numbers =
+            |numbers: &[i32]| Value::array(numbers.iter().map(|n| Value::number(*n)).collect());
+
+        assert_eq!(interpreter.environment.get("a"), numbers(&[0, 1, 2, 3]));
+        assert_eq!(interpreter.environment.get("b"), numbers(&[2, 3, 4]));
+        assert_eq!(interpreter.environment.get("c"), numbers(&[0, 3, 6, 9]));
+        assert_eq!(interpreter.environment.get("d"), numbers(&[5, 3, 1]));
+        assert_eq!(interpreter.environment.get("e"), numbers(&[]));
+
+        assert_eq!(
+            run_error("range(0, 5, 0);"),
+            RuntimeError::type_error("range() step 0 never reaches 5 from 0")
+        );
+        assert_eq!(
+            run_error("range(0, 5, -1);"),
+            RuntimeError::type_error("range() step -1 never reaches 5 from 0")
+        );
+        assert_eq!(
+            run_error("range();"),
+            RuntimeError::type_error("range() expects 1 to 3 arguments, got 0")
+        );
+        assert_eq!(
+            run_error("range(100000000000);"),
+            RuntimeError::type_error("range() would produce more than 10000000 numbers")
+        );
+        assert_eq!(
+            run_error("range(0, 1, 10 ** -300);"),
+            RuntimeError::type_error("range() would produce more than 10000000 numbers")
+        );
+    }
+
+    #[test]
+    fn bool_native() {
+        let output = run_with_output(
+            "println(bool(0), bool(NaN), bool(null), bool(undefined), bool(\"x\"), bool([]), bool({}), bool(bool));",
+        );
+
+        assert_eq!(output, "false false false false true true true true\n");
+
+        // unlike JS, the empty string is truthy here
+        let output = run_with_output("println(bool(\"\"));");
+
+        assert_eq!(output, "true\n");
+    }
+
+    #[test]
+    fn format_native() {
+        let interpreter = run_interpreter(
+            "
+        let a = format(\"{} + {} = {}\", 1, 2, 1 + 2);
+        let b = format(\"{{}} {{{}}} }}\", [1]);
+        let c = format(\"no placeholders\");",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::string("1 + 2 = 3"));
+        assert_eq!(
+            interpreter.environment.get("b"),
+            Value::string("{} {[1]} }")
+        );
+        assert_eq!(
+            interpreter.environment.get("c"),
+            Value::string("no placeholders")
+        );
+        assert_eq!(
+            run_error("format(\"{} {}\", 1);"),
+            RuntimeError::type_error("format() has 2 placeholders but got 1 arguments")
+        );
+        assert_eq!(
+            run_error("format(\"{}\", 1, 2);"),
+            RuntimeError::type_error("format() has 1 placeholders but got 2 arguments")
+        );
+        assert_eq!(
+            run_error("format(\"{ }\");"),
+            RuntimeError::type_error(
+                "format() found an unmatched '{', write '{{' for a literal brace"
+            )
+        );
+    }
+
+    #[test]
+    fn read_line_native() {
+        let environment = Rc::new(Environment::new());
+        let statements =
+            Parser::new("let a = read_line(), b = read_line(), c = read_line(), d = read_line();")
+                .parse()
+                .unwrap();
+
+        let mut interpreter = Interpreter::new(statements);
+        interpreter.set_input(Box::new(io::Cursor::new("first\nsecond\r\n\n")));
+        interpreter.run(&environment).unwrap();
+
+        assert_eq!(environment.get("a"), Ok(Value::string("first")));
+        assert_eq!(environment.get("b"), Ok(Value::string("second")));
+        assert_eq!(environment.get("c"), Ok(Value::string("")));
+        assert_eq!(environment.get("d"), Ok(Value::Null));
+
+        let statements = Parser::new("read_line();").parse().unwrap();
+        let mut interpreter = Interpreter::new(statements);
+        interpreter.set_input(Box::new(io::Cursor::new(vec![0xff, b'\n'])));
+
+        assert!(matches!(
+            interpreter.run(&environment),
+            Err(RuntimeError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn top_level_return() {
+        let buffer = SharedBuffer::default();
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new("println(\"before\"); return 1; println(\"after\");")
+            .parse()
+            .unwrap();
+
+        let result =
+            Interpreter::with_writer(statements, Box::new(buffer.clone())).run(&environment);
+
+        assert_eq!(result, Ok(Value::Number(1.0)));
+        assert_eq!(buffer.0.borrow().as_slice(), b"before\n");
+        assert_eq!(
+            Interpreter::eval_source("1; return; 2"),
+            Ok(Value::Undefined)
+        );
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_errors() {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new("println(1);").parse().unwrap();
+        let mut interpreter = Interpreter::with_writer(statements, Box::new(FailingWriter));
+
+        assert_eq!(
+            interpreter.run(&environment),
+            Err(RuntimeError::Io(
+                "Could not write to output: broken pipe".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn read_file_native() {
+        let path = format!("{}/Cargo.toml", env!("CARGO_MANIFEST_DIR"));
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(format!(
+            "let contents = read_file(\"{}\"); let missing = read_file(\"{}.missing\");",
+            path, path
+        ))
+        .parse()
+        .unwrap();
+
+        let mut interpreter = Interpreter::new(statements);
+        interpreter.set_allow_fs(true);
+
+        assert!(matches!(
+            interpreter.run(&environment),
+            Err(RuntimeError::Io(_))
+        ));
+        assert_eq!(
+            environment.get("contents"),
+            Ok(Value::String(std::fs::read_to_string(&path).unwrap()))
+        );
+    }
+
+    #[test]
+    fn read_file_needs_permission() {
+        assert_eq!(
+            run_error("read_file(\"Cargo.toml\");"),
+            RuntimeError::PermissionDenied("read_file".to_string())
+        );
+    }
+
+    #[test]
+    fn string_natives() {
+        let interpreter = run_interpreter(
+            "
+        let a = upper(\"Héllo\");
+        let b = lower(\"HéLLO\");
+        let c = trim(\"  a b \\n\");
+        let d = split(\"a,b,c\", \",\");
+        let e = split(\"héy\", \"\");
+        let f = split(\"a, b\", \", \");
+        let g = split(\"\", \",\");",
+        );
+
+        let strings = |strings: &[&str]| {
+            Value::array(
+                strings
+                    .iter()
+                    .map(|string| Value::string(*string))
+                    .collect(),
+            )
+        };
+
+        assert_eq!(interpreter.environment.get("a"), Value::string("HÉLLO"));
+        assert_eq!(interpreter.environment.get("b"), Value::string("héllo"));
+        assert_eq!(interpreter.environment.get("c"), Value::string("a b"));
+        assert_eq!(interpreter.environment.get("d"), strings(&["a", "b", "c"]));
+        assert_eq!(interpreter.environment.get("e"), strings(&["h", "é", "y"]));
+        assert_eq!(interpreter.environment.get("f"), strings(&["a", "b"]));
+        assert_eq!(interpreter.environment.get("g"), strings(&[""]));
+        assert_eq!(
+            run_error("split(\"a\", 1);"),
+            RuntimeError::type_error("split() expects a string, got 1")
+        );
+    }
+
+    #[test]
+    fn keys_and_values() {
+        let output = run_with_output(
+            "
+        let o = { b: 1, a: [2] };
+        o.c = 3;
+        o.b = 4;
+
+        println(keys(o), values(o), keys({}), o);",
+        );
+
+        assert_eq!(output, "[b, a, c] [4, [2], 3] [] { b: 4, a: [2], c: 3 }\n");
+        assert_eq!(
+            run_error("keys([1]);"),
+            RuntimeError::type_error("keys() expects an object, got [1]")
+        );
+        assert_eq!(
+            run_error("values(\"s\");"),
+            RuntimeError::type_error("values() expects an object, got \"s\"")
+        );
+    }
+
+    #[test]
+    fn clone_native() {
+        let output = run_with_output(
+            "
+        let original = { list: [1, { a: 1 }], name: \"o\" };
+        let copy = clone(original);
+
+        copy.list[1].a = 2;
+        push(copy.list, 3);
+        original.name = \"changed\";
+
+        println(original, copy, clone(1), clone(\"s\"));
+
+        let cyclic = [1];
+        push(cyclic, cyclic);
+        let cyclic_copy = clone(cyclic);
+        push(cyclic_copy, 2);
+
+        println(len(cyclic), len(cyclic_copy), len(cyclic_copy[1]), cyclic_copy[1] === cyclic_copy);",
+        );
+
+        assert_eq!(
+            output,
+            "{ list: [1, { a: 1 }], name: changed } { list: [1, { a: 2 }, 3], name: o } 1 s\n2 3 3 true\n"
+        );
+    }
+
+    #[test]
+    fn json_natives() {
+        let interpreter = run_interpreter(
+            "
+        let original = { scores: [1, 2.5, -3], done: true, name: \"a \\\"b\\\"\" };
+        let json = json_stringify(original);
+        let parsed = json_parse(json);",
+        );
+
+        assert_eq!(
+            interpreter.environment.get("json"),
+            Value::string(r#"{"scores":[1,2.5,-3],"done":true,"name":"a \"b\""}"#)
+        );
+        assert_eq!(
+            interpreter.environment.get("parsed"),
+            interpreter.environment.get("original")
+        );
+        assert_eq!(
+            run_error("json_stringify({ f: len });"),
+            RuntimeError::type_error("Cannot convert function len to JSON")
+        );
+        assert_eq!(
+            run_error("json_parse(\"{\");"),
+            RuntimeError::type_error("Invalid JSON: unexpected end of input at position 1")
+        );
+        assert_eq!(
+            run_error("json_parse(1);"),
+            RuntimeError::type_error("json_parse() expects a string, got 1")
+        );
+    }
+
+    #[test]
+    fn assertions() {
+        let output = run_with_output(
+            "
+        assert(1 < 2);
+        assert_eq(1 + 1, 2);
+        assert_eq(\"1\", 1);
+        assert_eq([1, [2, 3]], [1, [2, 3]]);
+        assert_eq({ a: [1], b: { c: \"d\" } }, { a: [1], b: { c: \"d\" } });
+        println(\"done\");",
+        );
+
+        assert_eq!(output, "done\n");
+        assert_eq!(
+            run_error("assert_eq([1, [2, 3]], [1, [2, 4]]);"),
+            RuntimeError::AssertionFailed("[1, [2, 3]] != [1, [2, 4]]".to_string())
+        );
+        assert_eq!(
+            run_error("assert_eq({ a: { b: 1 } }, { a: { b: 2 } });").to_string(),
+            "Assertion failed: { a: { b: 1 } } != { a: { b: 2 } }"
+        );
+        assert_eq!(
+            run_error("assert(1 > 2);"),
+            RuntimeError::AssertionFailed("false is not truthy".to_string())
+        );
+        assert_eq!(
+            run_error("assert_eq([1], \"a\");"),
+            RuntimeError::AssertionFailed("[1] != \"a\"".to_string())
+        );
+        assert_eq!(
+            run_error("assert(null);").to_string(),
+            "Assertion failed: null is not truthy"
+        );
+    }
+
+    #[test]
+    fn variable_assignment() {
+        let interpreter = run_interpreter("let x = 1; x = 2;");
+
+        assert_eq!(interpreter.environment.get("x"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn variable_assignment_with_undefined_variable() {
+        assert_eq!(
+            run_error("x = 2;"),
+            RuntimeError::UndefinedVariable("x".to_string())
+        );
+    }
+
+    #[test]
+    fn typeof_operator() {
+        let interpreter = run_interpreter(
+            "
+        function f() {}
+        let number = typeof 1;
+        let string = typeof \"a\";
+        let boolean = typeof true;
+        let null_ = typeof null;
+        let function_ = typeof f;
+        let native = typeof len;
+        let array = typeof [1];
+        let object = typeof {};
+        let undeclared = typeof missing;
+        let nested = typeof typeof 1;
+        let compared = typeof 1 == \"number\";",
+        );
+
+        assert_eq!(
+            interpreter.environment.get("number"),
+            Value::string("number")
+        );
+        assert_eq!(
+            interpreter.environment.get("string"),
+            Value::string("string")
+        );
+        assert_eq!(
+            interpreter.environment.get("boolean"),
+            Value::string("boolean")
+        );
+        assert_eq!(interpreter.environment.get("null_"), Value::string("null"));
+        assert_eq!(
+            interpreter.environment.get("function_"),
+            Value::string("function")
+        );
+        assert_eq!(
+            interpreter.environment.get("native"),
+            Value::string("function")
+        );
+        assert_eq!(
+            interpreter.environment.get("array"),
+            Value::string("object")
+        );
+        assert_eq!(
+            interpreter.environment.get("object"),
+            Value::string("object")
+        );
+        assert_eq!(
+            interpreter.environment.get("undeclared"),
+            Value::string("undefined")
+        );
+        assert_eq!(
+            interpreter.environment.get("nested"),
+            Value::string("string")
+        );
+        assert_eq!(interpreter.environment.get("compared"), Value::Bool(true));
+    }
+
+    #[test]
+    fn catch_thrown_string() {
+        let interpreter = run_interpreter(
+            "
+        let caught;
+        let after = false;
+
+        try {
+            throw \"boom\";
+            after = true;
+        } catch (e) {
+            caught = e;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("caught"), Value::string("boom"));
+        assert_eq!(interpreter.environment.get("after"), Value::Bool(false));
+        assert!(!interpreter.environment.has("e"));
+    }
+
+    #[test]
+    fn catch_thrown_from_function() {
+        let interpreter = run_interpreter(
+            "
+        function fail(value) {
+            throw { reason: value };
+        }
+
+        let reason;
+        let message;
+
+        try {
+            fail(42);
+        } catch (e) {
+            reason = e.reason;
+        }
+
+        try {
+            missing();
+        } catch (e) {
+            message = e;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("reason"), Value::Number(42.0));
+        assert_eq!(
+            interpreter.environment.get("message"),
+            Value::string("Undefined variable: missing")
+        );
+    }
+
+    #[test]
+    fn finally_runs_after_success_and_throw() {
+        let interpreter = run_interpreter(
+            "
+        let log = \"\";
+
+        try {
+            log = log + \"try \";
+        } finally {
+            log = log + \"finally \";
+        }
+
+        try {
+            throw 1;
+        } catch {
+            log = log + \"catch \";
+        } finally {
+            log = log + \"finally\";
+        }
+
+        function early() {
+            try {
+                return \"try\";
+            } finally {
+                log = log + \"!\";
+            }
+        }
+
+        let returned = early();",
+        );
+
+        assert_eq!(
+            interpreter.environment.get("log"),
+            Value::string("try finally catch finally!")
+        );
+        assert_eq!(
+            interpreter.environment.get("returned"),
+            Value::string("try")
+        );
+    }
+
+    #[test]
+    fn uncaught_throw() {
+        let error = run_error("try { throw \"boom\"; } finally {}");
+
+        assert_eq!(error, RuntimeError::Thrown(Value::string("boom")));
+        assert_eq!(error.to_string(), "Uncaught boom");
+    }
+
+    #[test]
+    fn exit_stops_the_script() {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(
+            "
+        let before = 1;
+        try { exit(2); } catch (e) {}
+        let after = 1;",
+        )
+        .parse()
+        .unwrap();
+
+        let result = Interpreter::new(statements).run(&environment);
+
+        assert_eq!(result, Err(RuntimeError::Exit(2)));
+        assert!(environment.has("before"));
+        assert!(!environment.has("after"));
+    }
+
+    #[test]
+    fn finally_cannot_swallow_exit() {
+        let buffer = SharedBuffer::default();
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(
+            "
+        function f() { try { exit(2); } finally { return 1; } }
+        f();
+        println(\"still running\");",
+        )
+        .parse()
+        .unwrap();
+
+        let result =
+            Interpreter::with_writer(statements, Box::new(buffer.clone())).run(&environment);
+
+        assert_eq!(result, Err(RuntimeError::Exit(2)));
+        assert!(buffer.0.borrow().is_empty());
+
+        let interpreter =
+            run_interpreter("function g() { try { throw 1; } finally { return 2; } } let a = g();");
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn const_declaration() {
+        let interpreter = run_interpreter("const a = 1; { let a = 2; a = 3; }");
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
+    }
+
+    #[test]
+    fn const_reassignment() {
+        assert_eq!(
+            run_error("const a = 1; a = 2;"),
+            RuntimeError::ConstantAssignment("a".to_string())
+        );
+        assert_eq!(
+            run_error("const a = 1; function f() { a = 2; } f();"),
+            RuntimeError::ConstantAssignment("a".to_string())
+        );
+    }
+
+    #[test]
+    fn undefined_variable() {
+        assert_eq!(
+            run_error("let a = 1; println(a + b);"),
+            RuntimeError::UndefinedVariable("b".to_string())
+        );
+    }
+
+    #[test]
+    fn type_errors() {
+        assert_eq!(
+            run_error("let a = 1 - \"a\";"),
+            RuntimeError::type_error("Cannot subtract string from number")
+        );
+        assert_eq!(
+            run_error("let a = \"a\" - 1;"),
+            RuntimeError::type_error("Cannot subtract number from string")
+        );
+        assert_eq!(
+            run_error("let a = -true;"),
+            RuntimeError::type_error("Cannot convert true to number")
+        );
+        assert_eq!(
+            run_error("let a = 1; a();"),
+            RuntimeError::type_error("Can only call functions and classes, got 1")
+        );
+    }
+
+    #[test]
+    fn function_arity_mismatch() {
+        let error = run_error("function f(a, b) { return a; } f(1, 2, 3);");
+
+        assert_eq!(
+            error,
+            RuntimeError::ArityMismatch {
+                expected: 2,
+                got: 3
+            }
+        );
+        assert_eq!(error.to_string(), "Expected 2 arguments but got 3");
     }
 
     #[test]
-    fn variable_assignment() {
-        let interpreter = run_interpreter("let x = 1; x = 2;");
+    fn errors_inside_functions_propagate() {
+        assert_eq!(
+            run_error("function f() { while (true) { return missing; } } f();"),
+            RuntimeError::UndefinedVariable("missing".to_string())
+        );
+    }
+
+    #[test]
+    fn if_statement() {
+        let interpreter = run_interpreter("let x = 1; if (true) { x = 2; }");
 
         assert_eq!(interpreter.environment.get("x"), Value::Number(2.0));
     }
 
     #[test]
-    #[should_panic(expected = "Undefined variable: x")]
-    fn variable_assignment_with_undefined_variable() {
-        run_interpreter("x = 2;");
+    fn else_if_chain() {
+        let output = run_with_output(
+            "
+        function sign(n) {
+            if (n < 0) {
+                return \"negative\";
+            } else if (n == 0) {
+                return \"zero\";
+            } else {
+                return \"positive\";
+            }
+        }
+
+        function nested(a, b) {
+            if (a) if (b) return \"both\"; else return \"only a\";
+            return \"not a\";
+        }
+
+        println(sign(-1), sign(0), sign(1));
+        println(nested(true, true), nested(true, false), nested(false, true));",
+        );
+
+        assert_eq!(output, "negative zero positive\nboth only a not a\n");
     }
 
     #[test]
-    fn if_statement() {
-        let interpreter = run_interpreter("let x = 1; if (true) { x = 2; }");
+    fn block_scope() {
+        let interpreter = run_interpreter(
+            "
+        let outer = 1;
 
-        assert_eq!(interpreter.environment.get("x"), Value::Number(2.0));
+        {
+            let inner = 2;
+            outer = inner;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("outer"), Value::Number(2.0));
+        assert!(!interpreter.environment.has("inner"));
+    }
+
+    #[test]
+    fn block_shadowing() {
+        let interpreter = run_interpreter(
+            "
+        let a = \"outer\";
+        let seen;
+
+        {
+            let a = \"inner\";
+            seen = a;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::string("outer"));
+        assert_eq!(interpreter.environment.get("seen"), Value::string("inner"));
+    }
+
+    #[test]
+    fn for_loop_variable_is_scoped_to_the_loop() {
+        let interpreter = run_interpreter("for (let i = 0; i < 3; i = i + 1) {}");
+
+        assert!(!interpreter.environment.has("i"));
+    }
+
+    #[test]
+    fn for_loop_closures_capture_their_own_iteration() {
+        let interpreter = run_interpreter(
+            "
+        let closures = [];
+
+        for (let i = 0; i < 3; i = i + 1) {
+            push(closures, function() { return i; });
+        }
+
+        let a = closures[0]();
+        let b = closures[1]();
+        let c = closures[2]();",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(0.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn for_loop_body_updates_carry_over() {
+        let interpreter = run_interpreter(
+            "
+        let runs = 0;
+
+        for (let i = 0; i < 10; i = i + 1) {
+            i = i + 1;
+            runs = runs + 1;
+        }",
+        );
+
+        assert_eq!(interpreter.environment.get("runs"), Value::Number(5.0));
     }
 
     #[test]
@@ -281,6 +2970,119 @@ mod tests {
         assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
     }
 
+    #[test]
+    fn functions_without_a_value_return_undefined() {
+        let interpreter = run_interpreter(
+            "
+        function f() {}
+        function g() { return; }
+        function h(a) { if (a) return 1; }
+        let a = f() === undefined;
+        let b = g() === undefined;
+        let c = h(false) === undefined;",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("b"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("c"), Value::Bool(true));
+    }
+
+    #[test]
+    fn return_inside_if() {
+        let interpreter = run_interpreter(
+            "
+        function sign(n) {
+            if (n < 0) {
+                return -1;
+            } else if (n == 0) {
+                return 0;
+            }
+
+            return 1;
+        }
+
+        let a = sign(-5);
+        let b = sign(0);
+        let c = sign(5);",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(-1.0));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(0.0));
+        assert_eq!(interpreter.environment.get("c"), Value::Number(1.0));
+    }
+
+    #[test]
+    fn return_inside_while() {
+        let interpreter = run_interpreter(
+            "
+        let iterations = 0;
+
+        function find(target) {
+            let i = 0;
+
+            while (true) {
+                iterations = iterations + 1;
+
+                if (i == target) {
+                    return i * 10;
+                }
+
+                i = i + 1;
+            }
+
+            return -1;
+        }
+
+        let a = find(3);",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(30.0));
+        assert_eq!(
+            interpreter.environment.get("iterations"),
+            Value::Number(4.0)
+        );
+    }
+
+    #[test]
+    fn return_inside_nested_block() {
+        let interpreter = run_interpreter(
+            "
+        let reached = false;
+
+        function foo() {
+            {
+                {
+                    return 1;
+                }
+            }
+
+            reached = true;
+            return 2;
+        }
+
+        let a = foo();",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
+        assert_eq!(interpreter.environment.get("reached"), Value::Bool(false));
+    }
+
+    #[test]
+    fn return_inside_for() {
+        let interpreter = run_interpreter(
+            "
+        function firstOver(limit) {
+            for (let i = 0; i < 100; i = i + 1) {
+                if (i * i > limit) return i;
+            }
+        }
+
+        let a = firstOver(50);",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Number(8.0));
+    }
+
     #[test]
     fn closures() {
         let interpreter = run_interpreter(
@@ -305,6 +3107,171 @@ mod tests {
         assert_eq!(interpreter.environment.get("b"), Value::Number(2.0));
     }
 
+    #[test]
+    fn closures_share_captured_variables() {
+        let output = run_with_output(
+            "
+        function makeCounter() {
+            let count = 0;
+
+            function inc() {
+                count = count + 1;
+            }
+
+            function get() {
+                return count;
+            }
+
+            return { inc: inc, get: get };
+        }
+
+        let a = makeCounter();
+        let b = makeCounter();
+
+        a.inc();
+        a.inc();
+        b.inc();
+
+        println(a.get(), b.get());",
+        );
+
+        assert_eq!(output, "2 1\n");
+    }
+
+    #[test]
+    fn closures_see_declarations_later_in_their_scope() {
+        let output = run_with_output(
+            "
+        let a = \"global\";
+        let i = 0;
+
+        while (i < 2) {
+            function show() {
+                println(a);
+            }
+
+            let a = \"loop\";
+            show();
+
+            i = i + 1;
+        }
+
+        function outer() {
+            function inner() {
+                return x;
+            }
+
+            let x = 1;
+
+            return inner();
+        }
+
+        println(outer());",
+        );
+
+        assert_eq!(output, "loop\nloop\n1\n");
+        assert_eq!(
+            run_error("{ function show() { return a; } show(); let a = 1; }"),
+            RuntimeError::UndefinedVariable("a".to_string())
+        );
+    }
+
+    #[test]
+    fn functions_can_call_functions_declared_after_them() {
+        let interpreter = run_interpreter(
+            "
+        function check(n) {
+            function isEven(n) {
+                if (n == 0) return true;
+                return isOdd(n - 1);
+            }
+
+            function isOdd(n) {
+                if (n == 0) return false;
+                return isEven(n - 1);
+            }
+
+            return isEven(n);
+        }
+
+        let a = check(4);
+        let b = check(7);",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("b"), Value::Bool(false));
+    }
+
+    #[test]
+    fn infinity_and_nan() {
+        let output = run_with_output(
+            "
+        println(1 / 0, -1 / 0, 0 / 0, 1 / 0 == Infinity, typeof NaN, NaN == NaN);
+
+        if (NaN) {
+            println(\"truthy\");
+        } else {
+            println(\"falsy\");
+        }",
+        );
+
+        assert_eq!(output, "Infinity -Infinity NaN true number false\nfalsy\n");
+    }
+
+    #[test]
+    fn recursive_functions_see_their_own_name() {
+        let interpreter = run_interpreter(
+            "
+        function fact(n) {
+            return n <= 1 ? 1 : n * fact(n - 1);
+        }
+
+        function outer() {
+            function count(n) {
+                return n == 0 ? 0 : 1 + count(n - 1);
+            }
+
+            return count(3);
+        }
+
+        let a = fact(5) == 120;
+        let b = outer();",
+        );
+
+        assert_eq!(interpreter.environment.get("a"), Value::Bool(true));
+        assert_eq!(interpreter.environment.get("b"), Value::Number(3.0));
+    }
+
+    #[test]
+    fn anonymous_functions_take_the_name_they_are_bound_to() {
+        let output = run_with_output(
+            "
+        let f = function() { return 1; };
+        let g;
+        g = function(x) { return x; };
+        let h = f;
+        let named = function inner() {};
+        println(f, g, h, named, f() + g(1));",
+        );
+
+        assert_eq!(
+            output,
+            "<function f()> <function g(x)> <function f()> <function inner()> 2\n"
+        );
+    }
+
+    #[test]
+    fn printed_functions_show_their_parameters() {
+        let output = run_with_output(
+            "function add(x, y) {} println(add, str(add), len, function(x) {}, [function() {}]);",
+        );
+
+        assert_eq!(
+            output,
+            "<function add(x, y)> <function add(x, y)> <function len> <function (x)> [<function ()>]\n"
+        );
+    }
+
     #[test]
     fn let_functions() {
         let interpreter = run_interpreter(
@@ -318,4 +3285,171 @@ mod tests {
 
         assert_eq!(interpreter.environment.get("a"), Value::Number(1.0));
     }
+
+    #[test]
+    fn runaway_recursion() {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(
+            "
+        function f() {
+            return f();
+        }
+
+        f();",
+        )
+        .parse()
+        .unwrap();
+
+        let mut interpreter = Interpreter::new(statements);
+        interpreter.set_max_call_depth(32);
+
+        assert_eq!(
+            interpreter.run(&environment),
+            Err(RuntimeError::StackOverflow)
+        );
+    }
+
+    #[test]
+    fn runaway_recursion_under_default_limit() {
+        let overflowed = with_stack(|| {
+            let environment = Rc::new(Environment::new());
+            let statements = Parser::new(
+                "
+            function f(n) {
+                return 1 + f(n + 1);
+            }
+
+            f(0);",
+            )
+            .parse()
+            .unwrap();
+
+            return Interpreter::new(statements).run(&environment)
+                == Err(RuntimeError::StackOverflow);
+        });
+
+        assert!(overflowed);
+    }
+
+    #[test]
+    fn recursion_just_below_default_limit() {
+        let result = with_stack(|| {
+            let environment = Rc::new(Environment::new());
+            let statements = Parser::new(format!(
+                "
+            function f(n) {{
+                if (n == 0) {{
+                    return 0;
+                }}
+
+                return 1 + f(n - 1);
+            }}
+
+            let result = f({});",
+                DEFAULT_MAX_CALL_DEPTH - 1
+            ))
+            .parse()
+            .unwrap();
+
+            Interpreter::new(statements).run(&environment).unwrap();
+
+            return environment.get("result").unwrap().display();
+        });
+
+        assert_eq!(result, (DEFAULT_MAX_CALL_DEPTH - 1).to_string());
+    }
+
+    #[test]
+    fn budget_stops_infinite_loops() {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(
+            "
+        let i = 0;
+
+        while (true) {
+            try {
+                i = i + 1;
+            } catch (e) {}
+        }",
+        )
+        .parse()
+        .unwrap();
+
+        let mut interpreter = Interpreter::with_budget(statements, 1000);
+
+        assert_eq!(
+            interpreter.run(&environment),
+            Err(RuntimeError::BudgetExceeded)
+        );
+        assert!(matches!(environment.get("i"), Ok(Value::Number(n)) if n > 0.0));
+    }
+
+    #[test]
+    fn loop_limit_stops_long_loops() {
+        let run = |code: &str| {
+            let environment = Rc::new(Environment::new());
+            let statements = Parser::new(code).parse().unwrap();
+
+            let mut interpreter = Interpreter::new(statements);
+            interpreter.set_max_loop_iterations(100);
+
+            (
+                interpreter.run(&environment),
+                EnvironmentHelper { environment },
+            )
+        };
+
+        let (result, environment) = run("
+        let i = 0;
+        try {
+            for (let j = 0; j < 1000000; j++) { i++; }
+        } catch (e) {}");
+
+        assert_eq!(result, Err(RuntimeError::LoopLimitExceeded(100)));
+        assert_eq!(environment.get("i"), Value::Number(100.0));
+
+        let (result, _) = run("let i = 0; do { i++; } while (i < 1000000);");
+
+        assert_eq!(result, Err(RuntimeError::LoopLimitExceeded(100)));
+
+        // the limit applies to each loop on its own, not to all iterations together
+        let (result, environment) = run("
+        let total = 0;
+        for (let i = 0; i < 50; i++) {
+            for (let j = 0; j < 50; j++) { total++; }
+        }");
+
+        assert_eq!(result, Ok(Value::Null));
+        assert_eq!(environment.get("total"), Value::Number(2500.0));
+    }
+
+    #[test]
+    fn call_depth_is_restored_after_errors() {
+        let environment = Rc::new(Environment::new());
+        let statements = Parser::new(
+            "
+        function f(n) {
+            if (n == 0) throw \"done\";
+            return f(n - 1);
+        }
+
+        let caught = 0;
+        let i = 0;
+        while (i < 10) {
+            try { f(5); } catch (e) { caught = caught + 1; }
+            i = i + 1;
+        }",
+        )
+        .parse()
+        .unwrap();
+
+        let mut interpreter = Interpreter::new(statements);
+        interpreter.set_max_call_depth(8);
+        interpreter.run(&environment).unwrap();
+
+        assert_eq!(
+            EnvironmentHelper { environment }.get("caught"),
+            Value::Number(10.0)
+        );
+    }
 }
@@ -0,0 +1,525 @@
+use std::rc::Rc;
+
+use crate::{
+    error::RuntimeError,
+    properties::Properties,
+    value::{format_number, Value},
+};
+
+/**
+ * How deeply arrays and objects may nest in parsed JSON, each level uses native stack
+ */
+const MAX_DEPTH: usize = 256;
+
+/**
+ * Converts a value to compact JSON like `JSON.stringify` does: non-finite numbers and undefined
+ * become null, and object properties keep their order. Functions and circular structures have no
+ * JSON representation and are errors
+ */
+pub fn stringify(value: &Value) -> Result<String, RuntimeError> {
+    let mut json = String::new();
+
+    write_value(value, &mut json, &mut Vec::new())?;
+
+    return Ok(json);
+}
+
+fn write_value(
+    value: &Value,
+    json: &mut String,
+    ancestors: &mut Vec<usize>,
+) -> Result<(), RuntimeError> {
+    match value {
+        Value::Number(number) if number.is_finite() => json.push_str(&format_number(*number)),
+        Value::Number(_) | Value::Null | Value::Undefined => json.push_str("null"),
+        Value::Bool(bool) => json.push_str(if *bool { "true" } else { "false" }),
+        Value::String(string) => write_string(string, json),
+        Value::Function(function) => {
+            return Err(RuntimeError::type_error(format!(
+                "Cannot convert function {} to JSON",
+                function.name()
+            )));
+        }
+        Value::Array(array) => {
+            enter(Rc::as_ptr(array) as usize, ancestors)?;
+            json.push('[');
+
+            for (i, element) in array.borrow().iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+
+                write_value(element, json, ancestors)?;
+            }
+
+            json.push(']');
+            ancestors.pop();
+        }
+        Value::Object(object) => {
+            enter(Rc::as_ptr(object) as usize, ancestors)?;
+            json.push('{');
+
+            for (i, (key, value)) in object.borrow().iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+
+                write_string(key, json);
+                json.push(':');
+                write_value(value, json, ancestors)?;
+            }
+
+            json.push('}');
+            ancestors.pop();
+        }
+    }
+
+    return Ok(());
+}
+
+/**
+ * Tracks the arrays and objects being written, so one that contains itself is an error instead of
+ * endless output
+ */
+fn enter(pointer: usize, ancestors: &mut Vec<usize>) -> Result<(), RuntimeError> {
+    if ancestors.contains(&pointer) {
+        return Err(RuntimeError::type_error(
+            "Cannot convert circular structure to JSON",
+        ));
+    }
+
+    ancestors.push(pointer);
+
+    return Ok(());
+}
+
+fn write_string(string: &str, json: &mut String) {
+    json.push('"');
+
+    for ch in string.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '\u{8}' => json.push_str("\\b"),
+            '\u{c}' => json.push_str("\\f"),
+            ch if (ch as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+
+    json.push('"');
+}
+
+/**
+ * Parses JSON text into the value it describes, objects keep the order of their keys and a
+ * repeated key keeps its last value
+ */
+pub fn parse(source: &str) -> Result<Value, RuntimeError> {
+    let mut parser = JsonParser {
+        chars: source.chars().collect(),
+        position: 0,
+        depth: 0,
+    };
+
+    let value = parser.value()?;
+
+    parser.skip_whitespace();
+
+    if parser.position < parser.chars.len() {
+        return Err(parser.unexpected());
+    }
+
+    return Ok(value);
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    position: usize,
+    depth: usize,
+}
+
+impl JsonParser {
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.position).copied();
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek();
+
+        if ch.is_some() {
+            self.position += 1;
+        }
+
+        return ch;
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), RuntimeError> {
+        if self.peek() != Some(expected) {
+            return Err(self.unexpected());
+        }
+
+        self.position += 1;
+
+        return Ok(());
+    }
+
+    fn error<S: Into<String>>(&self, message: S) -> RuntimeError {
+        return RuntimeError::type_error(format!(
+            "Invalid JSON: {} at position {}",
+            message.into(),
+            self.position
+        ));
+    }
+
+    fn unexpected(&self) -> RuntimeError {
+        match self.peek() {
+            Some(ch) => self.error(format!("unexpected '{}'", ch)),
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    // value -> object | array | string | number | "true" | "false" | "null"
+    fn value(&mut self) -> Result<Value, RuntimeError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => return self.nested(JsonParser::object),
+            Some('[') => return self.nested(JsonParser::array),
+            Some('"') => return Ok(Value::String(self.string()?)),
+            Some('-' | '0'..='9') => return self.number(),
+            Some('t') => return self.literal("true", Value::Bool(true)),
+            Some('f') => return self.literal("false", Value::Bool(false)),
+            Some('n') => return self.literal("null", Value::Null),
+            _ => return Err(self.unexpected()),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut JsonParser) -> Result<Value, RuntimeError>,
+    ) -> Result<Value, RuntimeError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        return value;
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, RuntimeError> {
+        for expected in literal.chars() {
+            self.expect(expected)?;
+        }
+
+        return Ok(value);
+    }
+
+    // object -> "{" ( string ":" value ( "," string ":" value )* )? "}"
+    fn object(&mut self) -> Result<Value, RuntimeError> {
+        let mut properties = Properties::new();
+
+        self.expect('{')?;
+        self.skip_whitespace();
+
+        if self.peek() == Some('}') {
+            self.position += 1;
+
+            return Ok(Value::object(properties));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            if self.peek() != Some('"') {
+                return Err(self.unexpected());
+            }
+
+            let key = self.string()?;
+
+            self.skip_whitespace();
+            self.expect(':')?;
+
+            properties.insert(key, self.value()?);
+
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => {
+                    self.position += 1;
+
+                    return Ok(Value::object(properties));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    // array -> "[" ( value ( "," value )* )? "]"
+    fn array(&mut self) -> Result<Value, RuntimeError> {
+        let mut elements = Vec::new();
+
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        if self.peek() == Some(']') {
+            self.position += 1;
+
+            return Ok(Value::array(elements));
+        }
+
+        loop {
+            elements.push(self.value()?);
+
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+
+                    return Ok(Value::array(elements));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    // string -> '"' ( char | "\" escape )* '"'
+    fn string(&mut self) -> Result<String, RuntimeError> {
+        let mut string = String::new();
+
+        self.expect('"')?;
+
+        loop {
+            let ch = match self.peek() {
+                Some(ch) if (ch as u32) < 0x20 => {
+                    return Err(self.error("control character in string"))
+                }
+                Some(ch) => ch,
+                None => return Err(self.error("unterminated string")),
+            };
+
+            self.position += 1;
+
+            match ch {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                ch => string.push(ch),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, RuntimeError> {
+        let ch = match self.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let unit = self.code_unit()?;
+
+                // characters outside the basic plane are written as a surrogate pair
+                if (0xD800..0xDC00).contains(&unit)
+                    && self.chars[self.position..].starts_with(&['\\', 'u'])
+                {
+                    self.position += 2;
+
+                    let low = self.code_unit()?;
+
+                    if (0xDC00..0xE000).contains(&low) {
+                        let code_point = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+
+                        return Ok(char::from_u32(code_point).unwrap());
+                    }
+                }
+
+                return char::from_u32(unit).ok_or_else(|| self.error("lone surrogate in string"));
+            }
+            Some(_) => {
+                self.position -= 1;
+
+                return Err(self.error("invalid escape sequence"));
+            }
+            None => return Err(self.error("unterminated string")),
+        };
+
+        return Ok(ch);
+    }
+
+    fn code_unit(&mut self) -> Result<u32, RuntimeError> {
+        let mut unit = 0;
+
+        for _ in 0..4 {
+            match self.peek().and_then(|ch| ch.to_digit(16)) {
+                Some(digit) => unit = unit * 16 + digit,
+                None => return Err(self.error("invalid unicode escape")),
+            }
+
+            self.position += 1;
+        }
+
+        return Ok(unit);
+    }
+
+    // number -> "-"? ( "0" | [1-9] [0-9]* ) ( "." [0-9]+ )? ( [eE] [+-]? [0-9]+ )?
+    fn number(&mut self) -> Result<Value, RuntimeError> {
+        let start = self.position;
+
+        if self.peek() == Some('-') {
+            self.position += 1;
+        }
+
+        match self.peek() {
+            Some('0') => self.position += 1,
+            Some('1'..='9') => self.digits()?,
+            _ => return Err(self.unexpected()),
+        }
+
+        if self.peek() == Some('.') {
+            self.position += 1;
+            self.digits()?;
+        }
+
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.position += 1;
+
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.position += 1;
+            }
+
+            self.digits()?;
+        }
+
+        let number: String = self.chars[start..self.position].iter().collect();
+
+        return Ok(Value::Number(number.parse().unwrap()));
+    }
+
+    fn digits(&mut self) -> Result<(), RuntimeError> {
+        if !matches!(self.peek(), Some('0'..='9')) {
+            return Err(self.unexpected());
+        }
+
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.position += 1;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stringify_values() {
+        let object = Value::object(Properties::from([
+            ("b".to_string(), Value::Number(1.5)),
+            ("a".to_string(), Value::string("say \"hi\"\n")),
+            (
+                "c".to_string(),
+                Value::array(vec![Value::Null, Value::Number(f64::NAN), Value::Undefined]),
+            ),
+        ]));
+
+        assert_eq!(
+            stringify(&object),
+            Ok(r#"{"b":1.5,"a":"say \"hi\"\n","c":[null,null,null]}"#.to_string())
+        );
+        assert_eq!(
+            stringify(&Value::string("\u{1}")),
+            Ok(r#""\u0001""#.to_string())
+        );
+    }
+
+    #[test]
+    fn stringify_circular_structure() {
+        let array = Value::array(vec![]);
+        let shared = Value::array(vec![Value::Number(1.0)]);
+
+        if let Value::Array(elements) = &array {
+            elements.borrow_mut().push(shared.clone());
+            elements.borrow_mut().push(shared);
+        }
+
+        assert_eq!(stringify(&array), Ok("[[1],[1]]".to_string()));
+
+        if let Value::Array(elements) = &array {
+            elements.borrow_mut().push(array.clone());
+        }
+
+        assert_eq!(
+            stringify(&array),
+            Err(RuntimeError::type_error(
+                "Cannot convert circular structure to JSON"
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(
+            parse(r#" { "a": [1, -2.5e1, true, null], "b": "\u00e9\ud83d\ude00\n", "a": {} } "#),
+            Ok(Value::object(Properties::from([
+                ("a".to_string(), Value::object(Properties::new())),
+                ("b".to_string(), Value::string("é😀\n")),
+            ])))
+        );
+        assert_eq!(parse("[]"), Ok(Value::array(vec![])));
+        assert_eq!(parse("0.5"), Ok(Value::Number(0.5)));
+    }
+
+    #[test]
+    fn parse_invalid_json() {
+        let cases = [
+            ("", "unexpected end of input at position 0"),
+            ("[1,]", "unexpected ']' at position 3"),
+            ("{\"a\" 1}", "unexpected '1' at position 5"),
+            ("{a: 1}", "unexpected 'a' at position 1"),
+            ("01", "unexpected '1' at position 1"),
+            ("1.", "unexpected end of input at position 2"),
+            ("tru", "unexpected end of input at position 3"),
+            ("\"\\x\"", "invalid escape sequence at position 2"),
+            ("\"\\ud800\"", "lone surrogate in string at position 7"),
+            ("\"abc", "unterminated string at position 4"),
+            ("[1] 2", "unexpected '2' at position 4"),
+        ];
+
+        for (source, message) in cases {
+            assert_eq!(
+                parse(source),
+                Err(RuntimeError::type_error(format!(
+                    "Invalid JSON: {}",
+                    message
+                ))),
+                "{}",
+                source
+            );
+        }
+
+        assert_eq!(
+            parse(&"[".repeat(1000)),
+            Err(RuntimeError::type_error(
+                "Invalid JSON: nested too deeply at position 256"
+            ))
+        );
+    }
+}
@@ -1,5 +1,11 @@
-mod callable;
+pub mod callable;
+mod control_flow;
 pub mod environment;
-mod functions;
+pub mod error;
+pub mod functions;
 pub mod interpreter;
-mod value;
+pub mod json;
+pub mod properties;
+pub mod repl;
+mod resolver;
+pub mod value;
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crate::value::Value;
+
+/**
+ * The properties of an object. Like in JS they are iterated in the order they were first added,
+ * assigning to an existing property keeps its place
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Properties {
+    entries: Vec<(String, Value)>,
+    indexes: HashMap<String, usize>,
+}
+
+impl Properties {
+    pub fn new() -> Properties {
+        Properties {
+            entries: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        return self.indexes.get(name).map(|&index| &self.entries[index].1);
+    }
+
+    pub fn insert(&mut self, name: String, value: Value) {
+        match self.indexes.get(&name) {
+            Some(&index) => self.entries[index].1 = value,
+            None => {
+                self.indexes.insert(name.clone(), self.entries.len());
+                self.entries.push((name, value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        return self.entries.iter().map(|(name, value)| (name, value));
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        return self.entries.iter().map(|(name, _)| name);
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        return self.entries.iter().map(|(_, value)| value);
+    }
+}
+
+impl FromIterator<(String, Value)> for Properties {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        let mut properties = Properties::new();
+
+        for (name, value) in iter {
+            properties.insert(name, value);
+        }
+
+        return properties;
+    }
+}
+
+impl<const N: usize> From<[(String, Value); N]> for Properties {
+    fn from(entries: [(String, Value); N]) -> Self {
+        return entries.into_iter().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_insertion_order() {
+        let mut properties = Properties::from([
+            ("b".to_string(), Value::Number(1.0)),
+            ("a".to_string(), Value::Number(2.0)),
+        ]);
+
+        properties.insert("c".to_string(), Value::Number(3.0));
+        properties.insert("b".to_string(), Value::Number(4.0));
+
+        assert_eq!(properties.keys().collect::<Vec<_>>(), ["b", "a", "c"]);
+        assert_eq!(
+            properties.values().cloned().collect::<Vec<_>>(),
+            [Value::Number(4.0), Value::Number(2.0), Value::Number(3.0)]
+        );
+        assert_eq!(properties.get("b"), Some(&Value::Number(4.0)));
+        assert_eq!(properties.get("d"), None);
+        assert_eq!(properties.len(), 3);
+    }
+}
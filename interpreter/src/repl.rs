@@ -0,0 +1,71 @@
+use std::rc::Rc;
+
+use parser::parser::Parser;
+
+use crate::{
+    environment::Environment, error::RuntimeError, interpreter::Interpreter, value::Value,
+};
+
+/**
+ * An interactive session, every line runs in the same global environment so definitions persist
+ */
+pub struct Repl {
+    environment: Rc<Environment>,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Repl {
+        Repl {
+            environment: Rc::new(Environment::new()),
+        }
+    }
+
+    /**
+     * Runs one line of input, yielding the value of its last expression statement. Errors leave
+     * the session usable and keep whatever the line defined before failing
+     */
+    pub fn eval_line(&mut self, line: &str) -> Result<Value, RuntimeError> {
+        let statements = Parser::new(line).parse()?;
+
+        return Interpreter::new(statements).run(&self.environment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_persists_across_lines() {
+        let mut repl = Repl::new();
+
+        assert_eq!(repl.eval_line("let x = 1;"), Ok(Value::Null));
+        assert_eq!(repl.eval_line("x + 1"), Ok(Value::Number(2.0)));
+        assert_eq!(
+            repl.eval_line("function double(n) { return n * 2; }"),
+            Ok(Value::Null)
+        );
+        assert_eq!(repl.eval_line("double(x)"), Ok(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn errors_do_not_end_the_session() {
+        let mut repl = Repl::new();
+
+        assert!(matches!(
+            repl.eval_line("let x = (1 +"),
+            Err(RuntimeError::Parse(_))
+        ));
+        assert_eq!(
+            repl.eval_line("let y = 2; y + missing"),
+            Err(RuntimeError::UndefinedVariable("missing".to_string()))
+        );
+        assert_eq!(repl.eval_line("y"), Ok(Value::Number(2.0)));
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashSet;
+
+use parser::{
+    expression::{Expression, Property},
+    ident::Ident,
+    statements::{block::BlockStatement, statement::Statement},
+    value::ParserValue,
+};
+
+/**
+ * Walks the AST before it runs and records, on every variable read and assignment, how many
+ * scopes separate it from the declaration it refers to. The scopes mirror the environments the
+ * interpreter creates at runtime, globals are not tracked and stay unresolved
+ */
+pub struct Resolver {
+    scopes: Vec<Scope>,
+    /// Index of the scope holding the body of the innermost function being resolved
+    function_scope: usize,
+}
+
+#[derive(Default)]
+struct Scope {
+    declared: HashSet<String>,
+    /// `let` and `const` names declared later in the scope, which only functions nested in it can
+    /// refer to since they run after the declaration
+    hoisted: HashSet<String>,
+}
+
+impl Resolver {
+    pub fn new() -> Resolver {
+        Resolver {
+            scopes: Vec::new(),
+            function_scope: 0,
+        }
+    }
+
+    pub fn resolve(&mut self, statements: &mut [Statement]) {
+        self.hoist(statements);
+
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::Let(stmt) => {
+                for (pattern, expression) in &mut stmt.declarations {
+                    if let Some(expression) = expression {
+                        self.expression(expression);
+                    }
+
+                    for ident in pattern.idents() {
+                        self.declare(ident);
+                    }
+                }
+            }
+            Statement::If(stmt) => {
+                self.expression(&mut stmt.condition);
+                self.statement(&mut stmt.consequence);
+
+                if let Some(alternative) = &mut stmt.alternative {
+                    self.statement(alternative);
+                }
+            }
+            Statement::While(stmt) => {
+                // the interpreter runs each iteration of a `for` loop in a scope of its own
+                // holding the loop variables
+                let scoped = !stmt.bindings.is_empty();
+
+                if scoped {
+                    self.scopes.push(Scope::default());
+
+                    for ident in &stmt.bindings {
+                        self.declare(ident);
+                    }
+                }
+
+                self.expression(&mut stmt.condition);
+                self.statement(&mut stmt.body);
+
+                if let Some(increment) = &mut stmt.increment {
+                    self.expression(increment);
+                }
+
+                if scoped {
+                    self.scopes.pop();
+                }
+            }
+            Statement::DoWhile(stmt) => {
+                self.statement(&mut stmt.body);
+                self.expression(&mut stmt.condition);
+            }
+            Statement::Switch(stmt) => {
+                self.expression(&mut stmt.discriminant);
+
+                for case in &mut stmt.cases {
+                    if let Some(test) = &mut case.test {
+                        self.expression(test);
+                    }
+                }
+
+                // every case body shares a single scope
+                self.scopes.push(Scope::default());
+
+                for case in &mut stmt.cases {
+                    self.resolve(&mut case.body);
+                }
+
+                self.scopes.pop();
+            }
+            Statement::Try(stmt) => {
+                self.block(&mut stmt.block, &[]);
+
+                if let Some(catch) = &mut stmt.catch {
+                    let param = catch.param.iter().cloned().collect::<Vec<Ident>>();
+
+                    self.block(&mut catch.body, &param);
+                }
+
+                if let Some(finally) = &mut stmt.finally {
+                    self.block(finally, &[]);
+                }
+            }
+            Statement::Block(stmt) => self.block(stmt, &[]),
+            Statement::Function(stmt) => {
+                self.declare(&stmt.ident);
+                self.function(&mut stmt.body, &stmt.parameters);
+            }
+            Statement::Throw(expression)
+            | Statement::Expression(expression)
+            | Statement::Return(Some(expression)) => self.expression(expression),
+            Statement::Labeled(stmt) => self.statement(&mut stmt.body),
+            Statement::Return(None) | Statement::Break(_) | Statement::Continue(_) => {}
+        }
+    }
+
+    fn expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Variable { ident, depth } => *depth = self.depth(ident),
+            Expression::This { depth } => *depth = self.depth(&Ident::new("this")),
+            Expression::Assignement {
+                ident,
+                value,
+                depth,
+            } => {
+                self.expression(value);
+                *depth = self.depth(ident);
+            }
+            Expression::Literal(ParserValue::Function { params, body, .. }) => {
+                self.function(body, params);
+            }
+            Expression::Literal(_) => {}
+            Expression::Grouping(expression) => self.expression(expression),
+            Expression::Array(elements) => {
+                for element in elements {
+                    self.expression(element);
+                }
+            }
+            Expression::Object(properties) => {
+                for property in properties {
+                    match property {
+                        Property::Named(_, value) | Property::Spread(value) => {
+                            self.expression(value)
+                        }
+                    }
+                }
+            }
+            Expression::Spread(expression) => self.expression(expression),
+            Expression::Unary { right, .. } => self.expression(right),
+            Expression::Update { target, .. } => self.expression(target),
+            Expression::Binary { .. } => {
+                // walks the left operands in a loop like the interpreter does, recursing into
+                // every one of them would overflow the stack on long chains
+                let mut rights = Vec::new();
+                let mut leftmost = expression;
+
+                while let Expression::Binary { left, right, .. } = leftmost {
+                    rights.push(right);
+                    leftmost = left;
+                }
+
+                self.expression(leftmost);
+
+                for right in rights.into_iter().rev() {
+                    self.expression(right);
+                }
+            }
+            Expression::Logical { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::Call { callee, arguments } => {
+                self.expression(callee);
+
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+            Expression::Get { object, .. } => self.expression(object),
+            Expression::Set { object, value, .. } => {
+                self.expression(object);
+                self.expression(value);
+            }
+            Expression::Index { object, index } => {
+                self.expression(object);
+                self.expression(index);
+            }
+            Expression::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expression(condition);
+                self.expression(then_branch);
+                self.expression(else_branch);
+            }
+        }
+    }
+
+    /**
+     * Resolves `block` in a new scope that starts out with `names` declared, function parameters
+     * and catch parameters live in the same scope as the body they belong to
+     */
+    fn block(&mut self, block: &mut BlockStatement, names: &[Ident]) {
+        self.scopes.push(Scope::default());
+
+        for name in names {
+            self.declare(name);
+        }
+
+        self.resolve(block.statements_mut());
+        self.scopes.pop();
+    }
+
+    /**
+     * Resolves a function body, whose scope also holds `this` next to the parameters
+     */
+    fn function(&mut self, body: &mut BlockStatement, parameters: &[Ident]) {
+        let mut names = vec![Ident::new("this")];
+        names.extend(parameters.iter().cloned());
+
+        let enclosing = std::mem::replace(&mut self.function_scope, self.scopes.len());
+
+        self.block(body, &names);
+        self.function_scope = enclosing;
+    }
+
+    /**
+     * Declares every function of a scope up front, so functions can call the ones declared after
+     * them in the same scope, and records the scope's `let` and `const` names so nested functions
+     * can refer to them before their declaration
+     */
+    fn hoist(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::Function(stmt) => self.declare(&stmt.ident),
+                Statement::Let(stmt) => {
+                    if let Some(scope) = self.scopes.last_mut() {
+                        for (pattern, _) in &stmt.declarations {
+                            scope
+                                .hoisted
+                                .extend(pattern.idents().iter().map(Ident::value));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn declare(&mut self, ident: &Ident) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.declared.insert(ident.value());
+        }
+    }
+
+    fn depth(&self, ident: &Ident) -> Option<usize> {
+        let name = ident.value();
+
+        return self
+            .scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .position(|(index, scope)| {
+                scope.declared.contains(&name)
+                    || (index < self.function_scope && scope.hoisted.contains(&name))
+            });
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::parser::Parser;
+
+    use super::*;
+
+    fn resolve(code: &str) -> Vec<Statement> {
+        let mut statements = Parser::new(code).parse().unwrap();
+
+        Resolver::new().resolve(&mut statements);
+
+        return statements;
+    }
+
+    #[test]
+    fn globals_stay_unresolved() {
+        let statements = resolve("let a = 1; a;");
+
+        assert_eq!(
+            statements[1],
+            Statement::_expression(Expression::variable("a"))
+        );
+    }
+
+    #[test]
+    fn locals() {
+        let statements = resolve("{ let a = 1; { a = a; } }");
+
+        let Statement::Block(outer) = &statements[0] else {
+            panic!("Expected a block");
+        };
+        let Statement::Block(inner) = &outer.statements()[1] else {
+            panic!("Expected a block");
+        };
+
+        assert_eq!(
+            inner.statements()[0],
+            Statement::_expression(Expression::Assignement {
+                ident: Ident::new("a"),
+                value: Box::new(Expression::Variable {
+                    ident: Ident::new("a"),
+                    depth: Some(1),
+                }),
+                depth: Some(1),
+            })
+        );
+    }
+
+    #[test]
+    fn functions_see_later_declarations() {
+        let statements = resolve("{ function f() { return [a, b]; } let a = 1; } let b = 2;");
+
+        let Statement::Block(block) = &statements[0] else {
+            panic!("Expected a block");
+        };
+        let Statement::Function(function) = &block.statements()[0] else {
+            panic!("Expected a function");
+        };
+
+        assert_eq!(
+            function.body.statements()[0],
+            Statement::_return(Expression::array(vec![
+                Expression::Variable {
+                    ident: Ident::new("a"),
+                    depth: Some(1),
+                },
+                Expression::variable("b"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn later_declarations_do_not_shadow_before_them() {
+        let statements = resolve("{ let a = 1; { a; let a = 2; } }");
+
+        let Statement::Block(outer) = &statements[0] else {
+            panic!("Expected a block");
+        };
+        let Statement::Block(inner) = &outer.statements()[1] else {
+            panic!("Expected a block");
+        };
+
+        assert_eq!(
+            inner.statements()[0],
+            Statement::_expression(Expression::Variable {
+                ident: Ident::new("a"),
+                depth: Some(1),
+            })
+        );
+    }
+}
@@ -1,37 +1,116 @@
 use core::fmt;
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
 
-use crate::callable::Callable;
+use crate::{callable::Callable, error::RuntimeError, properties::Properties};
 
 #[derive(Clone)]
 pub enum Value {
     Function(Box<dyn Callable>),
+    Array(Rc<RefCell<Vec<Value>>>),
+    Object(Rc<RefCell<Properties>>),
     Number(f64),
     String(String),
     Bool(bool),
     Null,
+    /// What missing things evaluate to: uninitialized bindings, absent properties and elements
+    Undefined,
 }
 
+/**
+ * Longest string, in bytes, that repeating a string may build, so a script can't exhaust memory
+ * with a single expression
+ */
+const MAX_STRING_LENGTH: usize = 1 << 30;
+
+/**
+ * Structural equality: arrays are equal when their elements are, objects when they have the same
+ * keys with equal values. Scripts compare arrays and objects by reference instead, see `strict_eq`
+ */
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Value::Number(number), Value::Number(other_number)) => number == other_number,
-            (Value::String(string), Value::String(other_string)) => string == other_string,
-            (Value::Bool(bool), Value::Bool(other_bool)) => bool == other_bool,
-            (Value::Null, Value::Null) => true,
-            _ => false,
-        }
+        return self.structural_eq(other, &mut Vec::new());
     }
 }
 
+/**
+ * Like `display`, but strings are quoted so diagnostics can tell `"1"` from `1`
+ */
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Value::Number(number) => write!(f, "{}", number),
-            Value::String(string) => write!(f, "{}", string),
-            Value::Bool(bool) => write!(f, "{}", bool),
-            Value::Null => write!(f, "null"),
-            Value::Function(function) => write!(f, "<function {}>", function.name()),
+        write!(f, "{}", self.format(true, &mut Vec::new()))
+    }
+}
+
+/**
+ * Converts an index to a position in an array or string, only non-negative integers are valid
+ */
+fn to_position(index: &Value) -> Result<usize, RuntimeError> {
+    match index {
+        Value::Number(number) if *number >= 0.0 && number.fract() == 0.0 => Ok(*number as usize),
+        _ => Err(RuntimeError::type_error(format!(
+            "Index must be a non-negative integer, got {:?}",
+            index
+        ))),
+    }
+}
+
+/**
+ * Formats a number the way JS's `Number.prototype.toString` does: integers without a fractional
+ * part, the shortest digits that round-trip, and exponent notation outside [1e-6, 1e21)
+ */
+pub fn format_number(number: f64) -> String {
+    if number.is_nan() {
+        return "NaN".to_string();
+    }
+
+    if number.is_infinite() {
+        return if number > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
         }
+        .to_string();
+    }
+
+    if number == 0.0 {
+        // also covers -0, which JS prints without a sign
+        return "0".to_string();
+    }
+
+    let magnitude = number.abs();
+
+    if (1e-6..1e21).contains(&magnitude) {
+        return format!("{}", number);
+    }
+
+    let formatted = format!("{:e}", number);
+
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) if !exponent.starts_with('-') => {
+            return format!("{}e+{}", mantissa, exponent);
+        }
+        _ => return formatted,
+    }
+}
+
+/**
+ * Converts a string to a number the way JS's `Number(string)` does, giving NaN when it isn't numeric
+ */
+pub fn string_to_number(string: &str) -> f64 {
+    let string = string.trim();
+
+    match string {
+        "" => 0.0,
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        // f64's parser also takes words like "inf" and "nan", which JS doesn't
+        _ if string
+            .chars()
+            .any(|ch| ch.is_ascii_alphabetic() && ch != 'e' && ch != 'E') =>
+        {
+            f64::NAN
+        }
+        _ => string.parse().unwrap_or(f64::NAN),
     }
 }
 
@@ -52,106 +131,573 @@ impl Value {
         Value::Null
     }
 
+    pub fn undefined() -> Self {
+        Value::Undefined
+    }
+
     pub fn function(function: Box<dyn Callable>) -> Self {
         Value::Function(function)
     }
 
-    pub fn to_number(&self) -> f64 {
+    /**
+     * The text `print` and `str` show for this value, numbers without a needless fractional part
+     * and with the shortest digits that round-trip, like JS
+     */
+    pub fn display(&self) -> String {
+        return self.format(false, &mut Vec::new());
+    }
+
+    /**
+     * Text for `display` and `Debug`. `visiting` holds the arrays and objects being printed, so
+     * one that contains itself prints as `[...]` or `{...}` instead of recursing forever
+     */
+    fn format(&self, quote: bool, visiting: &mut Vec<usize>) -> String {
         match self {
-            Value::Number(number) => *number,
-            _ => panic!("Cannot convert {:?} to number", self),
+            Value::Number(number) => format_number(*number),
+            Value::String(string) if quote => format!("{:?}", string),
+            Value::String(string) => string.clone(),
+            Value::Bool(bool) => bool.to_string(),
+            Value::Null => "null".to_string(),
+            Value::Undefined => "undefined".to_string(),
+            Value::Function(function) => format!("<function {}>", function.signature()),
+            Value::Array(array) => {
+                let address = Rc::as_ptr(array) as usize;
+
+                if visiting.contains(&address) {
+                    return "[...]".to_string();
+                }
+
+                visiting.push(address);
+                let elements = array
+                    .borrow()
+                    .iter()
+                    .map(|value| value.format(quote, visiting))
+                    .collect::<Vec<String>>();
+                visiting.pop();
+
+                format!("[{}]", elements.join(", "))
+            }
+            Value::Object(object) => {
+                let address = Rc::as_ptr(object) as usize;
+
+                if visiting.contains(&address) {
+                    return "{...}".to_string();
+                }
+
+                let object = object.borrow();
+
+                if object.is_empty() {
+                    return "{}".to_string();
+                }
+
+                visiting.push(address);
+                let properties = object
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value.format(quote, visiting)))
+                    .collect::<Vec<String>>();
+                visiting.pop();
+
+                format!("{{ {} }}", properties.join(", "))
+            }
+        }
+    }
+
+    pub fn as_callable(&self) -> Option<&dyn Callable> {
+        match self {
+            Value::Function(function) => Some(function.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub fn array(values: Vec<Value>) -> Self {
+        Value::Array(Rc::new(RefCell::new(values)))
+    }
+
+    pub fn object(properties: Properties) -> Self {
+        Value::Object(Rc::new(RefCell::new(properties)))
+    }
+
+    pub fn to_number(&self) -> Result<f64, RuntimeError> {
+        match self {
+            Value::Number(number) => Ok(*number),
+            _ => Err(RuntimeError::type_error(format!(
+                "Cannot convert {:?} to number",
+                self
+            ))),
+        }
+    }
+
+    /**
+     * The name `typeof` reports for this value
+     */
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Function(_) => "function",
+            Value::Array(_) | Value::Object(_) => "object",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Bool(_) => "boolean",
+            Value::Null => "null",
+            Value::Undefined => "undefined",
+        }
+    }
+
+    /**
+     * The kind of value this is, for error messages. Unlike `type_name` it tells arrays, objects
+     * and null apart
+     */
+    pub fn type_of(&self) -> &'static str {
+        match self {
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+            _ => self.type_name(),
         }
     }
 
     pub fn is_truthy(&self) -> bool {
         match self {
-            Value::Number(number) => *number != 0.0,
+            Value::Number(number) => *number != 0.0 && !number.is_nan(),
             Value::Bool(bool) => *bool,
-            Value::Null => false,
+            Value::Null | Value::Undefined => false,
+            Value::Array(_) | Value::Object(_) => true,
             _ => true,
         }
     }
 
+    /**
+     * Reads `self[index]`, returning undefined when the index is past the end
+     */
+    pub fn index(&self, index: &Value) -> Result<Value, RuntimeError> {
+        let position = to_position(index)?;
+
+        match self {
+            Value::Array(array) => Ok(array
+                .borrow()
+                .get(position)
+                .cloned()
+                .unwrap_or(Value::Undefined)),
+            Value::String(string) => Ok(string
+                .chars()
+                .nth(position)
+                .map(|ch| Value::String(ch.to_string()))
+                .unwrap_or(Value::Undefined)),
+            _ => Err(RuntimeError::type_error(format!(
+                "Can only index arrays and strings, got {:?}",
+                self
+            ))),
+        }
+    }
+
+    /**
+     * Replaces the element of an array at `index`, which must already exist
+     */
+    pub fn set_index(&self, index: &Value, value: Value) -> Result<(), RuntimeError> {
+        let position = to_position(index)?;
+
+        match self {
+            Value::Array(array) => {
+                let mut array = array.borrow_mut();
+                let length = array.len();
+
+                match array.get_mut(position) {
+                    Some(element) => *element = value,
+                    None => {
+                        return Err(RuntimeError::type_error(format!(
+                            "Index {} is out of bounds for an array of length {}",
+                            position, length
+                        )))
+                    }
+                }
+            }
+            _ => {
+                return Err(RuntimeError::type_error(format!(
+                    "Can only assign to indexes of arrays, got {:?}",
+                    self
+                )))
+            }
+        }
+
+        return Ok(());
+    }
+
+    /**
+     * Reads `self.name`, returning undefined when the object has no such property
+     */
+    pub fn get(&self, name: &str) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Object(object) => Ok(object
+                .borrow()
+                .get(name)
+                .cloned()
+                .unwrap_or(Value::Undefined)),
+            _ => Err(RuntimeError::type_error(format!(
+                "Cannot read property '{}' of non-object {:?}",
+                name, self
+            ))),
+        }
+    }
+
+    /**
+     * Whether `key` is a property of an object or a valid index of an array, what `key in self`
+     * evaluates to
+     */
+    pub fn has(&self, key: &Value) -> Result<Value, RuntimeError> {
+        let found = match (self, key) {
+            (Value::Object(object), Value::String(name)) => object.borrow().get(name).is_some(),
+            (Value::Object(object), Value::Number(number)) => {
+                object.borrow().get(&format_number(*number)).is_some()
+            }
+            (Value::Array(array), Value::Number(index)) => {
+                *index >= 0.0 && index.fract() == 0.0 && (*index as usize) < array.borrow().len()
+            }
+            (Value::Object(_) | Value::Array(_), _) => false,
+            _ => {
+                return Err(RuntimeError::type_error(format!(
+                    "Cannot use 'in' to search for {} in {}",
+                    key.type_of(),
+                    self.type_of()
+                )))
+            }
+        };
+
+        return Ok(Value::Bool(found));
+    }
+
+    pub fn set(&self, name: &str, value: Value) -> Result<(), RuntimeError> {
+        match self {
+            Value::Object(object) => {
+                object.borrow_mut().insert(name.to_string(), value);
+
+                Ok(())
+            }
+            _ => Err(RuntimeError::type_error(format!(
+                "Cannot set property '{}' on non-object {:?}",
+                name, self
+            ))),
+        }
+    }
+
     pub fn not(&self) -> Value {
         Value::Bool(!self.is_truthy())
     }
 
-    pub fn sum(&self, other: &Value) -> Value {
+    pub fn sum(&self, other: &Value) -> Result<Value, RuntimeError> {
         match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Number(left + right),
-            (Value::String(left), Value::String(right)) => {
-                Value::String(format!("{}{}", left, right))
+            (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left + right)),
+            // like JS, a string on either side turns + into concatenation
+            (Value::String(_), _) | (_, Value::String(_)) => {
+                Ok(Value::String(self.display() + &other.display()))
             }
-            _ => unimplemented!(),
+            _ => Err(self.unsupported("+", other)),
         }
     }
 
-    pub fn sub(&self, other: &Value) -> Value {
+    pub fn sub(&self, other: &Value) -> Result<Value, RuntimeError> {
         match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Number(left - right),
-            _ => unimplemented!(),
+            (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left - right)),
+            _ => Err(self.unsupported("-", other)),
         }
     }
 
-    pub fn mult(&self, other: &Value) -> Value {
+    pub fn mult(&self, other: &Value) -> Result<Value, RuntimeError> {
         match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Number(left * right),
-            _ => unimplemented!(),
+            (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left * right)),
+            (Value::String(string), Value::Number(count))
+            | (Value::Number(count), Value::String(string)) => {
+                // the count is truncated, negative and NaN counts repeat nothing
+                let times = count.max(0.0) as usize;
+                let length = string.len().checked_mul(times);
+
+                if *count == f64::INFINITY || length.is_none_or(|length| length > MAX_STRING_LENGTH)
+                {
+                    return Err(RuntimeError::type_error(format!(
+                        "Cannot repeat a string {} times",
+                        format_number(*count)
+                    )));
+                }
+
+                Ok(Value::String(string.repeat(times)))
+            }
+            _ => Err(self.unsupported("*", other)),
         }
     }
 
-    pub fn div(&self, other: &Value) -> Value {
+    pub fn div(&self, other: &Value) -> Result<Value, RuntimeError> {
         match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Number(left / right),
-            _ => unimplemented!(),
+            (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left / right)),
+            _ => Err(self.unsupported("/", other)),
         }
     }
 
-    pub fn gt(&self, other: &Value) -> Value {
+    pub fn modulo(&self, other: &Value) -> Result<Value, RuntimeError> {
         match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Bool(left > right),
-            (Value::String(left), Value::String(right)) => Value::Bool(left > right),
-            _ => unimplemented!(),
+            (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left % right)),
+            _ => Err(self.unsupported("%", other)),
         }
     }
 
-    pub fn lt(&self, other: &Value) -> Value {
+    pub fn pow(&self, other: &Value) -> Result<Value, RuntimeError> {
         match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Bool(left < right),
-            (Value::String(left), Value::String(right)) => Value::Bool(left < right),
-            _ => unimplemented!(),
+            (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left.powf(*right))),
+            _ => Err(self.unsupported("**", other)),
+        }
+    }
+
+    pub fn gt(&self, other: &Value) -> Result<Value, RuntimeError> {
+        let ordering = self.compare(">", other)?;
+
+        return Ok(Value::Bool(ordering == Some(Ordering::Greater)));
+    }
+
+    pub fn lt(&self, other: &Value) -> Result<Value, RuntimeError> {
+        let ordering = self.compare("<", other)?;
+
+        return Ok(Value::Bool(ordering == Some(Ordering::Less)));
+    }
+
+    pub fn gte(&self, other: &Value) -> Result<Value, RuntimeError> {
+        let ordering = self.compare(">", other)?;
+
+        return Ok(Value::Bool(matches!(
+            ordering,
+            Some(Ordering::Greater | Ordering::Equal)
+        )));
+    }
+
+    pub fn lte(&self, other: &Value) -> Result<Value, RuntimeError> {
+        let ordering = self.compare("<", other)?;
+
+        return Ok(Value::Bool(matches!(
+            ordering,
+            Some(Ordering::Less | Ordering::Equal)
+        )));
+    }
+
+    /**
+     * Orders two values for the relational operators like JS does: two strings compare by their
+     * characters, a string compared with a number and booleans are converted to numbers, so
+     * `"5" > 3` and chains like `1 < 2 < 3` work. `None` means the values are unordered, because
+     * one of them is NaN
+     */
+    fn compare(&self, operator: &str, other: &Value) -> Result<Option<Ordering>, RuntimeError> {
+        match (self, other) {
+            (Value::String(left), Value::String(right)) => Ok(Some(left.cmp(right))),
+            (Value::Number(_) | Value::Bool(_), Value::Number(_) | Value::Bool(_))
+            | (Value::String(_), Value::Number(_))
+            | (Value::Number(_), Value::String(_)) => Ok(self
+                .comparison_number()
+                .partial_cmp(&other.comparison_number())),
+            _ => Err(self.unsupported(operator, other)),
+        }
+    }
+
+    fn comparison_number(&self) -> f64 {
+        match self {
+            Value::Number(number) => *number,
+            Value::Bool(bool) => *bool as u8 as f64,
+            Value::String(string) => string_to_number(string),
+            _ => f64::NAN,
+        }
+    }
+
+    /**
+     * Converts to a 32 bit integer like JS's bitwise operators do: the fraction is dropped, the
+     * result wraps around, and NaN and infinities become 0
+     */
+    pub fn to_int32(&self) -> Result<i32, RuntimeError> {
+        let number = self.to_number()?;
+
+        if !number.is_finite() {
+            return Ok(0);
         }
+
+        return Ok((number.trunc() % 4294967296.0) as i64 as u32 as i32);
     }
 
-    pub fn gte(&self, other: &Value) -> Value {
-        return self.lt(other).not();
+    pub fn bit_and(&self, other: &Value) -> Result<Value, RuntimeError> {
+        return self.bitwise("&", other, |left, right| left & right);
     }
 
-    pub fn lte(&self, other: &Value) -> Value {
-        return self.gt(other).not();
+    pub fn bit_or(&self, other: &Value) -> Result<Value, RuntimeError> {
+        return self.bitwise("|", other, |left, right| left | right);
     }
 
-    pub fn eq(&self, other: &Value) -> Value {
+    pub fn bit_xor(&self, other: &Value) -> Result<Value, RuntimeError> {
+        return self.bitwise("^", other, |left, right| left ^ right);
+    }
+
+    /**
+     * Shifts by the right operand's lowest 5 bits, as JS does
+     */
+    pub fn shl(&self, other: &Value) -> Result<Value, RuntimeError> {
+        return self.bitwise("<<", other, |left, right| left.wrapping_shl(right as u32));
+    }
+
+    /**
+     * Sign-propagating right shift
+     */
+    pub fn shr(&self, other: &Value) -> Result<Value, RuntimeError> {
+        return self.bitwise(">>", other, |left, right| left.wrapping_shr(right as u32));
+    }
+
+    pub fn bit_not(&self) -> Result<Value, RuntimeError> {
+        return Ok(Value::Number(!self.to_int32()? as f64));
+    }
+
+    fn bitwise(
+        &self,
+        operator: &str,
+        other: &Value,
+        apply: fn(i32, i32) -> i32,
+    ) -> Result<Value, RuntimeError> {
         match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Bool(left == right),
-            (Value::String(left), Value::String(right)) => Value::Bool(left == right),
-            (Value::Bool(left), Value::Bool(right)) => Value::Bool(left == right),
-            (Value::Null, Value::Null) => Value::Bool(true),
-            (Value::Null, _) => Value::Bool(false),
-            _ => unimplemented!(),
+            (Value::Number(_), Value::Number(_)) => Ok(Value::Number(apply(
+                self.to_int32()?,
+                other.to_int32()?,
+            ) as f64)),
+            _ => Err(self.unsupported(operator, other)),
+        }
+    }
+
+    /**
+     * Loose equality (`==`): numbers compared with strings or booleans are compared numerically,
+     * and null and undefined only equal each other
+     */
+    pub fn eq(&self, other: &Value) -> Result<Value, RuntimeError> {
+        match (self, other) {
+            (Value::Null | Value::Undefined, Value::Null | Value::Undefined) => {
+                Ok(Value::Bool(true))
+            }
+            (Value::Number(left), Value::String(right)) => {
+                Ok(Value::Bool(*left == string_to_number(right)))
+            }
+            (Value::String(_), Value::Number(_)) => other.eq(self),
+            (Value::Bool(bool), _) if !matches!(other, Value::Bool(_)) => {
+                Value::Number(*bool as u8 as f64).eq(other)
+            }
+            (_, Value::Bool(_)) if !matches!(self, Value::Bool(_)) => other.eq(self),
+            _ => self.strict_eq(other),
         }
     }
 
-    pub fn neq(&self, other: &Value) -> Value {
-        return self.eq(other).not();
+    pub fn neq(&self, other: &Value) -> Result<Value, RuntimeError> {
+        return Ok(self.eq(other)?.not());
+    }
+
+    /**
+     * Strict equality (`===`): values of different types are never equal
+     */
+    pub fn strict_eq(&self, other: &Value) -> Result<Value, RuntimeError> {
+        let equal = match (self, other) {
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Null, Value::Null) => true,
+            (Value::Undefined, Value::Undefined) => true,
+            // arrays and objects are only equal to themselves, like JS references
+            (Value::Array(left), Value::Array(right)) => Rc::ptr_eq(left, right),
+            (Value::Object(left), Value::Object(right)) => Rc::ptr_eq(left, right),
+            (Value::Function(left), Value::Function(right)) => **left == **right,
+            _ => false,
+        };
+
+        return Ok(Value::Bool(equal));
+    }
+
+    pub fn strict_neq(&self, other: &Value) -> Result<Value, RuntimeError> {
+        return Ok(self.strict_eq(other)?.not());
     }
 
+    /**
+     * Like JS's `&&`, yields `self` when it is falsy and `other` otherwise
+     */
     pub fn and(&self, other: &Value) -> Value {
-        return Value::Bool(self.is_truthy() && other.is_truthy());
+        if !self.is_truthy() {
+            return self.clone();
+        }
+
+        return other.clone();
     }
 
+    /**
+     * Like JS's `||`, yields `self` when it is truthy and `other` otherwise
+     */
     pub fn or(&self, other: &Value) -> Value {
-        return Value::Bool(self.is_truthy() || other.is_truthy());
+        if self.is_truthy() {
+            return self.clone();
+        }
+
+        return other.clone();
+    }
+
+    /**
+     * `compared` holds the pairs of arrays and objects already being compared further up, meeting
+     * one of them again means the structures are cyclic in the same way, so it counts as equal
+     */
+    fn structural_eq(&self, other: &Value, compared: &mut Vec<(usize, usize)>) -> bool {
+        match (self, other) {
+            (Value::Number(number), Value::Number(other_number)) => number == other_number,
+            (Value::String(string), Value::String(other_string)) => string == other_string,
+            (Value::Bool(bool), Value::Bool(other_bool)) => bool == other_bool,
+            (Value::Null, Value::Null) => true,
+            (Value::Undefined, Value::Undefined) => true,
+            (Value::Array(array), Value::Array(other_array)) => {
+                let pair = (Rc::as_ptr(array) as usize, Rc::as_ptr(other_array) as usize);
+
+                if Rc::ptr_eq(array, other_array) || compared.contains(&pair) {
+                    return true;
+                }
+
+                let (array, other_array) = (array.borrow(), other_array.borrow());
+
+                compared.push(pair);
+                let equal = array.len() == other_array.len()
+                    && array
+                        .iter()
+                        .zip(other_array.iter())
+                        .all(|(value, other_value)| value.structural_eq(other_value, compared));
+                compared.pop();
+
+                equal
+            }
+            (Value::Object(object), Value::Object(other_object)) => {
+                let pair = (
+                    Rc::as_ptr(object) as usize,
+                    Rc::as_ptr(other_object) as usize,
+                );
+
+                if Rc::ptr_eq(object, other_object) || compared.contains(&pair) {
+                    return true;
+                }
+
+                let (object, other_object) = (object.borrow(), other_object.borrow());
+
+                compared.push(pair);
+                let equal = object.len() == other_object.len()
+                    && object.iter().all(|(key, value)| {
+                        other_object
+                            .get(key)
+                            .is_some_and(|other_value| value.structural_eq(other_value, compared))
+                    });
+                compared.pop();
+
+                equal
+            }
+            _ => false,
+        }
+    }
+
+    fn unsupported(&self, operator: &str, other: &Value) -> RuntimeError {
+        let (left, right) = (self.type_of(), other.type_of());
+
+        return RuntimeError::type_error(match operator {
+            "+" => format!("Cannot add {} to {}", right, left),
+            "-" => format!("Cannot subtract {} from {}", right, left),
+            "*" => format!("Cannot multiply {} by {}", left, right),
+            "/" => format!("Cannot divide {} by {}", left, right),
+            "<" | ">" => format!("Cannot compare {} with {}", left, right),
+            _ => format!("Cannot apply '{}' to {} and {}", operator, left, right),
+        });
     }
 }
 
@@ -159,33 +705,268 @@ impl Value {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_type_of() {
+        assert_eq!(Value::Number(1.0).type_of(), "number");
+        assert_eq!(Value::string("a").type_of(), "string");
+        assert_eq!(Value::Bool(true).type_of(), "boolean");
+        assert_eq!(Value::Null.type_of(), "null");
+        assert_eq!(Value::array(vec![]).type_of(), "array");
+        assert_eq!(Value::object(Properties::new()).type_of(), "object");
+    }
+
     #[test]
     fn test_is_truthy() {
-        assert_eq!(Value::Number(0.0).is_truthy(), false);
-        assert_eq!(Value::Number(1.0).is_truthy(), true);
-        assert_eq!(Value::Bool(false).is_truthy(), false);
-        assert_eq!(Value::Bool(true).is_truthy(), true);
-        assert_eq!(Value::Null.is_truthy(), false);
-        assert_eq!(Value::String("".to_string()).is_truthy(), true);
-        assert_eq!(Value::String("foo".to_string()).is_truthy(), true);
+        assert!(!Value::Number(0.0).is_truthy());
+        assert!(Value::Number(1.0).is_truthy());
+        assert!(!Value::Number(f64::NAN).is_truthy());
+        assert!(Value::Number(f64::INFINITY).is_truthy());
+        assert!(!Value::Bool(false).is_truthy());
+        assert!(Value::Bool(true).is_truthy());
+        assert!(!Value::Null.is_truthy());
+        assert!(!Value::Undefined.is_truthy());
+        assert!(Value::String("".to_string()).is_truthy());
+        assert!(Value::String("foo".to_string()).is_truthy());
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(-3.0), "-3");
+        assert_eq!(format_number(0.5), "0.5");
+        assert_eq!(format_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(
+            format_number(123456789012345680000.0),
+            "123456789012345680000"
+        );
+        assert_eq!(format_number(1e21), "1e+21");
+        assert_eq!(format_number(-1.5e300), "-1.5e+300");
+        assert_eq!(format_number(0.000001), "0.000001");
+        assert_eq!(format_number(1.5e-7), "1.5e-7");
+        assert_eq!(format_number(f64::NAN), "NaN");
+        assert_eq!(format_number(f64::INFINITY), "Infinity");
+        assert_eq!(format_number(f64::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_display() {
+        assert_eq!(Value::Number(3.0).display(), "3");
+        assert_eq!(Value::Number(3.14).display(), "3.14");
+        assert_eq!(Value::Number(1e21).display(), "1e+21");
+        assert_eq!(Value::Number(-0.0).display(), "0");
+        assert_eq!(
+            Value::array(vec![Value::Number(5.0), Value::Number(5.5)]).display(),
+            "[5, 5.5]"
+        );
+    }
+
+    #[test]
+    fn test_display_cycles() {
+        let array = Value::array(vec![Value::Number(1.0)]);
+
+        if let Value::Array(elements) = &array {
+            elements.borrow_mut().push(array.clone());
+        }
+
+        assert_eq!(array.display(), "[1, [...]]");
+
+        let object = Value::object(Properties::new());
+        object.set("self", object.clone()).unwrap();
+        object.set("list", array.clone()).unwrap();
+
+        assert_eq!(object.display(), "{ self: {...}, list: [1, [...]] }");
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(format!("{:?}", Value::string("1")), "\"1\"");
+        assert_eq!(
+            format!(
+                "{:?}",
+                Value::array(vec![Value::string("a\"b"), Value::Number(1.0)])
+            ),
+            "[\"a\\\"b\", 1]"
+        );
+        assert_eq!(Value::array(vec![Value::string("a")]).display(), "[a]");
+    }
+
+    #[test]
+    fn test_array() {
+        let array = Value::array(vec![Value::Number(1.0), Value::array(vec![])]);
+
+        assert!(array.is_truthy());
+        assert!(Value::array(vec![]).is_truthy());
+        assert_eq!(format!("{:?}", array), "[1, []]");
+        assert_eq!(
+            array,
+            Value::array(vec![Value::Number(1.0), Value::array(vec![])])
+        );
+        assert_ne!(array, Value::array(vec![Value::Number(1.0)]));
+        assert_ne!(
+            array,
+            Value::array(vec![Value::Number(1.0), Value::array(vec![Value::Null])])
+        );
+    }
+
+    #[test]
+    fn test_nested_equality() {
+        let object = |value: Value| {
+            Value::object(Properties::from([(
+                "a".to_string(),
+                Value::array(vec![Value::object(Properties::from([(
+                    "b".to_string(),
+                    value,
+                )]))]),
+            )]))
+        };
+
+        assert_eq!(object(Value::Number(1.0)), object(Value::Number(1.0)));
+        assert_ne!(object(Value::Number(1.0)), object(Value::Number(2.0)));
+        assert_ne!(object(Value::Number(1.0)), object(Value::Undefined));
+        assert_ne!(
+            object(Value::Null),
+            Value::object(Properties::from([("b".to_string(), Value::Null)]))
+        );
+    }
+
+    #[test]
+    fn test_cyclic_equality() {
+        let cyclic = |value: Value| {
+            let array = Value::array(vec![value]);
+
+            if let Value::Array(elements) = &array {
+                elements.borrow_mut().push(array.clone());
+            }
+
+            return array;
+        };
+
+        assert_eq!(cyclic(Value::Number(1.0)), cyclic(Value::Number(1.0)));
+        assert_ne!(cyclic(Value::Number(1.0)), cyclic(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn test_object() {
+        let object = Value::object(Properties::from([
+            ("b".to_string(), Value::Number(2.0)),
+            ("a".to_string(), Value::object(Properties::new())),
+        ]));
+
+        assert!(object.is_truthy());
+        assert_eq!(format!("{:?}", object), "{ b: 2, a: {} }");
+        assert_eq!(
+            object,
+            Value::object(Properties::from([
+                ("a".to_string(), Value::object(Properties::new())),
+                ("b".to_string(), Value::Number(2.0)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        let array = Value::array(vec![Value::Number(1.0), Value::Number(2.0)]);
+
+        assert_eq!(
+            array.index(&Value::Number(1.0)).unwrap(),
+            Value::Number(2.0)
+        );
+        assert_eq!(array.index(&Value::Number(2.0)).unwrap(), Value::Undefined);
+        assert_eq!(
+            Value::string("héllo").index(&Value::Number(1.0)).unwrap(),
+            Value::string("é")
+        );
+        assert_eq!(
+            Value::string("").index(&Value::Number(0.0)).unwrap(),
+            Value::Undefined
+        );
+    }
+
+    #[test]
+    fn test_negative_index() {
+        assert_eq!(
+            Value::array(vec![]).index(&Value::Number(-1.0)),
+            Err(RuntimeError::type_error(
+                "Index must be a non-negative integer, got -1"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_index_non_indexable() {
+        assert_eq!(
+            Value::Bool(true).index(&Value::Number(0.0)),
+            Err(RuntimeError::type_error(
+                "Can only index arrays and strings, got true"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unsupported_operands() {
+        assert_eq!(
+            Value::Number(1.0).sub(&Value::Bool(true)),
+            Err(RuntimeError::type_error(
+                "Cannot subtract boolean from number"
+            ))
+        );
+        assert_eq!(
+            Value::array(vec![]).mult(&Value::Null),
+            Err(RuntimeError::type_error("Cannot multiply array by null"))
+        );
+        assert_eq!(
+            Value::Number(1.0).modulo(&Value::object(Properties::new())),
+            Err(RuntimeError::type_error(
+                "Cannot apply '%' to number and object"
+            ))
+        );
+        assert_eq!(
+            Value::Null.to_number(),
+            Err(RuntimeError::type_error("Cannot convert null to number"))
+        );
     }
 
     #[test]
     fn test_sum() {
         assert_eq!(
-            Value::Number(1.0).sum(&Value::Number(2.0)),
+            Value::Number(1.0).sum(&Value::Number(2.0)).unwrap(),
             Value::Number(3.0)
         );
         assert_eq!(
-            Value::String("foo".to_string()).sum(&Value::String("bar".to_string())),
+            Value::String("foo".to_string())
+                .sum(&Value::String("bar".to_string()))
+                .unwrap(),
             Value::String("foobar".to_string())
         );
     }
 
+    #[test]
+    fn test_sum_mixed() {
+        assert_eq!(
+            Value::string("count: ").sum(&Value::Number(3.0)).unwrap(),
+            Value::string("count: 3")
+        );
+        assert_eq!(
+            Value::Number(1.0).sum(&Value::string("x")).unwrap(),
+            Value::string("1x")
+        );
+        assert_eq!(
+            Value::Bool(true).sum(&Value::string("!")).unwrap(),
+            Value::string("true!")
+        );
+        assert_eq!(
+            Value::Null.sum(&Value::string("")).unwrap(),
+            Value::string("null")
+        );
+        assert!(Value::Bool(true).sum(&Value::Number(1.0)).is_err());
+    }
+
     #[test]
     fn test_sub() {
         assert_eq!(
-            Value::Number(1.0).sub(&Value::Number(2.0)),
+            Value::Number(1.0).sub(&Value::Number(2.0)).unwrap(),
             Value::Number(-1.0)
         );
     }
@@ -193,60 +974,246 @@ mod tests {
     #[test]
     fn test_mult() {
         assert_eq!(
-            Value::Number(1.0).mult(&Value::Number(2.0)),
+            Value::Number(1.0).mult(&Value::Number(2.0)).unwrap(),
             Value::Number(2.0)
         );
+        assert_eq!(
+            Value::string("ab").mult(&Value::Number(3.0)).unwrap(),
+            Value::string("ababab")
+        );
+        assert_eq!(
+            Value::string("x").mult(&Value::Number(0.0)).unwrap(),
+            Value::string("")
+        );
+        assert_eq!(
+            Value::Number(3.0).mult(&Value::string("y")).unwrap(),
+            Value::string("yyy")
+        );
+        assert_eq!(
+            Value::string("ab").mult(&Value::Number(2.9)).unwrap(),
+            Value::string("abab")
+        );
+        assert_eq!(
+            Value::string("ab").mult(&Value::Number(-1.0)).unwrap(),
+            Value::string("")
+        );
+        assert_eq!(
+            Value::string("ab").mult(&Value::Number(f64::INFINITY)),
+            Err(RuntimeError::type_error(
+                "Cannot repeat a string Infinity times"
+            ))
+        );
+        assert_eq!(
+            Value::string("ab").mult(&Value::Number(1e20)),
+            Err(RuntimeError::type_error(
+                "Cannot repeat a string 100000000000000000000 times"
+            ))
+        );
+        assert_eq!(
+            Value::string("ab").mult(&Value::Number(1e9)),
+            Err(RuntimeError::type_error(
+                "Cannot repeat a string 1000000000 times"
+            ))
+        );
     }
 
     #[test]
     fn test_div() {
         assert_eq!(
-            Value::Number(1.0).div(&Value::Number(2.0)),
+            Value::Number(1.0).div(&Value::Number(2.0)).unwrap(),
             Value::Number(0.5)
         );
     }
 
+    #[test]
+    fn test_modulo() {
+        assert_eq!(
+            Value::Number(7.0).modulo(&Value::Number(3.0)).unwrap(),
+            Value::Number(1.0)
+        );
+        assert_eq!(
+            Value::Number(10.0).modulo(&Value::Number(2.0)).unwrap(),
+            Value::Number(0.0)
+        );
+        assert_eq!(
+            Value::Number(-7.0).modulo(&Value::Number(3.0)).unwrap(),
+            Value::Number(-1.0)
+        );
+        assert!(Value::Number(7.0)
+            .modulo(&Value::Number(0.0))
+            .unwrap()
+            .to_number()
+            .unwrap()
+            .is_nan());
+    }
+
+    #[test]
+    fn test_and_or() {
+        assert_eq!(Value::Null.or(&Value::Number(5.0)), Value::Number(5.0));
+        assert_eq!(
+            Value::string("x").and(&Value::Number(0.0)),
+            Value::Number(0.0)
+        );
+        assert_eq!(
+            Value::Number(0.0).and(&Value::string("x")),
+            Value::Number(0.0)
+        );
+        assert_eq!(Value::string("x").or(&Value::Null), Value::string("x"));
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(
+            Value::Number(2.0).pow(&Value::Number(10.0)).unwrap(),
+            Value::Number(1024.0)
+        );
+        assert!(Value::string("2").pow(&Value::Number(2.0)).is_err());
+    }
+
+    #[test]
+    fn test_to_int32() {
+        let int32 = |number: f64| Value::Number(number).to_int32().unwrap();
+
+        assert_eq!(int32(3.9), 3);
+        assert_eq!(int32(-3.9), -3);
+        assert_eq!(int32(4294967296.0 + 5.0), 5);
+        assert_eq!(int32(2147483648.0), -2147483648);
+        assert_eq!(int32(f64::NAN), 0);
+        assert_eq!(int32(f64::INFINITY), 0);
+        assert_eq!(
+            Value::Number(1.0).shl(&Value::Number(33.0)).unwrap(),
+            Value::Number(2.0)
+        );
+        assert!(Value::string("1").bit_and(&Value::Number(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_loose_eq() {
+        let loose = |left: Value, right: Value| left.eq(&right).unwrap();
+
+        assert_eq!(
+            loose(Value::Number(1.0), Value::string("1")),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            loose(Value::string("1.0"), Value::Number(1.0)),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            loose(Value::Number(0.0), Value::string("")),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            loose(Value::Bool(true), Value::Number(1.0)),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            loose(Value::string("1"), Value::Bool(true)),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            loose(Value::string("a"), Value::Number(0.0)),
+            Value::Bool(false)
+        );
+        assert_eq!(loose(Value::Null, Value::Null), Value::Bool(true));
+        assert_eq!(loose(Value::Null, Value::Number(0.0)), Value::Bool(false));
+        assert_eq!(loose(Value::Undefined, Value::Null), Value::Bool(true));
+        assert_eq!(
+            loose(Value::Undefined, Value::Bool(false)),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            loose(Value::Number(f64::NAN), Value::Number(f64::NAN)),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_strict_eq() {
+        let strict = |left: Value, right: Value| left.strict_eq(&right).unwrap();
+
+        assert_eq!(
+            strict(Value::Number(1.0), Value::string("1")),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            strict(Value::Number(1.0), Value::Number(1.0)),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            strict(Value::Bool(true), Value::Number(1.0)),
+            Value::Bool(false)
+        );
+        assert_eq!(strict(Value::Null, Value::Null), Value::Bool(true));
+        assert_eq!(strict(Value::Undefined, Value::Null), Value::Bool(false));
+        assert_eq!(
+            strict(Value::Undefined, Value::Undefined),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            Value::string("a").strict_neq(&Value::string("b")).unwrap(),
+            Value::Bool(true)
+        );
+    }
+
     #[test]
     fn test_to_number() {
-        assert_eq!(Value::Number(1.0).to_number(), 1.0);
+        assert_eq!(Value::Number(1.0).to_number().unwrap(), 1.0);
     }
 
     #[test]
     fn test_gt() {
         assert_eq!(
-            Value::Number(1.0).gt(&Value::Number(2.0)),
+            Value::Number(1.0).gt(&Value::Number(2.0)).unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::Number(2.0).gt(&Value::Number(1.0)),
+            Value::Number(2.0).gt(&Value::Number(1.0)).unwrap(),
             Value::Bool(true)
         );
         assert_eq!(
-            Value::String("foo".to_string()).gt(&Value::String("bar".to_string())),
+            Value::String("foo".to_string())
+                .gt(&Value::String("bar".to_string()))
+                .unwrap(),
             Value::Bool(true)
         );
         assert_eq!(
-            Value::String("bar".to_string()).gt(&Value::String("foo".to_string())),
+            Value::String("bar".to_string())
+                .gt(&Value::String("foo".to_string()))
+                .unwrap(),
             Value::Bool(false)
         );
+        assert_eq!(
+            Value::Bool(true).gt(&Value::Number(0.5)).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            Value::Bool(true).gt(&Value::Bool(false)).unwrap(),
+            Value::Bool(true)
+        );
+        assert!(Value::Bool(true).gt(&Value::string("1")).is_err());
     }
 
     #[test]
     fn test_lt() {
         assert_eq!(
-            Value::Number(1.0).lt(&Value::Number(2.0)),
+            Value::Number(1.0).lt(&Value::Number(2.0)).unwrap(),
             Value::Bool(true)
         );
         assert_eq!(
-            Value::Number(2.0).lt(&Value::Number(1.0)),
+            Value::Number(2.0).lt(&Value::Number(1.0)).unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::String("foo".to_string()).lt(&Value::String("bar".to_string())),
+            Value::String("foo".to_string())
+                .lt(&Value::String("bar".to_string()))
+                .unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::String("bar".to_string()).lt(&Value::String("foo".to_string())),
+            Value::String("bar".to_string())
+                .lt(&Value::String("foo".to_string()))
+                .unwrap(),
             Value::Bool(true)
         );
     }
@@ -254,19 +1221,23 @@ mod tests {
     #[test]
     fn test_gte() {
         assert_eq!(
-            Value::Number(1.0).gte(&Value::Number(2.0)),
+            Value::Number(1.0).gte(&Value::Number(2.0)).unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::Number(2.0).gte(&Value::Number(1.0)),
+            Value::Number(2.0).gte(&Value::Number(1.0)).unwrap(),
             Value::Bool(true)
         );
         assert_eq!(
-            Value::String("foo".to_string()).gte(&Value::String("bar".to_string())),
+            Value::String("foo".to_string())
+                .gte(&Value::String("bar".to_string()))
+                .unwrap(),
             Value::Bool(true)
         );
         assert_eq!(
-            Value::String("bar".to_string()).gte(&Value::String("foo".to_string())),
+            Value::String("bar".to_string())
+                .gte(&Value::String("foo".to_string()))
+                .unwrap(),
             Value::Bool(false)
         );
     }
@@ -274,19 +1245,23 @@ mod tests {
     #[test]
     fn test_lte() {
         assert_eq!(
-            Value::Number(1.0).lte(&Value::Number(2.0)),
+            Value::Number(1.0).lte(&Value::Number(2.0)).unwrap(),
             Value::Bool(true)
         );
         assert_eq!(
-            Value::Number(2.0).lte(&Value::Number(1.0)),
+            Value::Number(2.0).lte(&Value::Number(1.0)).unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::String("foo".to_string()).lte(&Value::String("bar".to_string())),
+            Value::String("foo".to_string())
+                .lte(&Value::String("bar".to_string()))
+                .unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::String("bar".to_string()).lte(&Value::String("foo".to_string())),
+            Value::String("bar".to_string())
+                .lte(&Value::String("foo".to_string()))
+                .unwrap(),
             Value::Bool(true)
         );
     }
@@ -294,22 +1269,33 @@ mod tests {
     #[test]
     fn test_eq() {
         assert_eq!(
-            Value::Number(1.0).eq(&Value::Number(2.0)),
+            Value::Number(1.0).eq(&Value::Number(2.0)).unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::Number(2.0).eq(&Value::Number(1.0)),
+            Value::Number(2.0).eq(&Value::Number(1.0)).unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::String("foo".to_string()).eq(&Value::String("bar".to_string())),
+            Value::String("foo".to_string())
+                .eq(&Value::String("bar".to_string()))
+                .unwrap(),
             Value::Bool(false)
         );
         assert_eq!(
-            Value::String("bar".to_string()).eq(&Value::String("foo".to_string())),
+            Value::String("bar".to_string())
+                .eq(&Value::String("foo".to_string()))
+                .unwrap(),
             Value::Bool(false)
         );
-        assert_eq!(Value::Null.eq(&Value::Null), Value::Bool(true));
-        assert_eq!(Value::Null.eq(&Value::Number(1.0)), Value::Bool(false));
+        assert_eq!(Value::Null.eq(&Value::Null).unwrap(), Value::Bool(true));
+        assert_eq!(
+            Value::Null.eq(&Value::Number(1.0)).unwrap(),
+            Value::Bool(false)
+        );
+        let array = Value::array(vec![]);
+
+        assert_eq!(array.eq(&array).unwrap(), Value::Bool(true));
+        assert_eq!(array.eq(&Value::array(vec![])).unwrap(), Value::Bool(false));
     }
 }
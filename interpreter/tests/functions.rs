@@ -0,0 +1,39 @@
+use interpreter::{
+    callable::{Arity, Callable},
+    functions::{js_function::JsFunction, native_function::NativeFunction},
+    repl::Repl,
+    value::Value,
+};
+use parser::ident::Ident;
+
+#[test]
+fn returned_functions_can_be_downcast() {
+    let mut repl = Repl::new();
+
+    repl.eval_line("function add(x, y) { return x + y; }")
+        .unwrap();
+
+    let Value::Function(function) = repl.eval_line("add").unwrap() else {
+        panic!("expected a function");
+    };
+
+    let function = function
+        .as_any()
+        .downcast_ref::<JsFunction>()
+        .expect("expected a function defined in the script");
+
+    assert_eq!(function.parameters(), &[Ident::new("x"), Ident::new("y")]);
+    assert_eq!(function.arity(), Arity::Fixed(2));
+}
+
+#[test]
+fn native_functions_can_be_downcast() {
+    let mut repl = Repl::new();
+
+    let Value::Function(function) = repl.eval_line("println").unwrap() else {
+        panic!("expected a function");
+    };
+
+    assert!(function.as_any().downcast_ref::<NativeFunction>().is_some());
+    assert!(function.as_any().downcast_ref::<JsFunction>().is_none());
+}
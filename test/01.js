@@ -2,7 +2,7 @@ let a = "global";
 let a = "not global";
   {
     function showA() {
-      print a;
+      println(a);
 		}
 
     showA();